@@ -0,0 +1,132 @@
+use alloc::vec::Vec;
+
+use super::{Bits, Select1};
+
+/// Growable array of bits, laid out from the least significant bit of each word.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BitBuf {
+    words: Vec<u64>,
+    len: usize,
+}
+
+fn mask(width: usize) -> u64 {
+    if width < 64 { (1 << width) - 1 } else { !0 }
+}
+
+impl BitBuf {
+    /// Copy the first `len` bits of `words`.
+    pub fn from_words(words: &[u64], len: usize) -> BitBuf {
+        assert!(len <= words.len() * 64);
+        let mut words = words[..len.div_ceil(64)].to_vec();
+        if !len.is_multiple_of(64) {
+            let last = words.len() - 1;
+            words[last] &= mask(len % 64);
+        }
+        BitBuf { words, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.words.len() * 8
+    }
+
+    /// Append the low `width` bits of `value`.
+    pub fn push(&mut self, value: u64, width: usize) {
+        debug_assert!(width <= 64);
+        if width == 0 {
+            return;
+        }
+        let value = value & mask(width);
+        let pos = self.len % 64;
+        if pos == 0 {
+            self.words.push(value);
+        } else {
+            let last = self.words.len() - 1;
+            self.words[last] |= value << pos;
+            if pos + width > 64 {
+                self.words.push(value >> (64 - pos));
+            }
+        }
+        self.len += width;
+    }
+
+    pub fn push_zeros(&mut self, n: usize) {
+        let mut n = n;
+        while n > 0 {
+            let width = n.min(64);
+            self.push(0, width);
+            n -= width;
+        }
+    }
+
+    pub fn set(&mut self, i: usize) {
+        debug_assert!(i < self.len);
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        debug_assert!(i < self.len);
+        self.words[i / 64] & 1 << (i % 64) != 0
+    }
+
+    /// Read `width` bits from `i`.
+    pub fn get_bits(&self, i: usize, width: usize) -> u64 {
+        debug_assert!(width <= 64 && i + width <= self.len);
+        if width == 0 {
+            return 0;
+        }
+        let (w, pos) = (i / 64, i % 64);
+        let mut bits = self.words[w] >> pos;
+        if pos + width > 64 {
+            bits |= self.words[w + 1] << (64 - pos);
+        }
+        bits & mask(width)
+    }
+
+    /// Overwrite `width` bits from `i` with the low bits of `value`.
+    pub fn set_bits(&mut self, i: usize, value: u64, width: usize) {
+        debug_assert!(width <= 64 && i + width <= self.len);
+        if width == 0 {
+            return;
+        }
+        let value = value & mask(width);
+        let (w, pos) = (i / 64, i % 64);
+        self.words[w] = self.words[w] & !(mask(width) << pos) | value << pos;
+        if pos + width > 64 {
+            let rest = pos + width - 64;
+            self.words[w + 1] = self.words[w + 1] & !mask(rest) | value >> (64 - pos);
+        }
+    }
+
+    /// Position of the 'c+1'th one, or zero if not `one`, in `from..to`.
+    pub fn select(&self, from: usize, to: usize, c: usize, one: bool) -> Option<usize> {
+        let (mut c, mut pos) = (c, from);
+        while pos < to {
+            let width = (to - pos).min(64);
+            let bits = self.get_bits(pos, width);
+            let bits = if one { bits } else { !bits & mask(width) };
+            let ones = bits.ones();
+            if c < ones {
+                return Select1::<usize>::select1(&bits, c).map(|p| pos + p);
+            }
+            c -= ones;
+            pos += width;
+        }
+        None
+    }
+
+    /// Count ones in `from..to`.
+    pub fn rank1(&self, from: usize, to: usize) -> usize {
+        let mut ones = 0;
+        let mut pos = from;
+        while pos < to {
+            let width = (to - pos).min(64);
+            ones += self.get_bits(pos, width).ones();
+            pos += width;
+        }
+        ones
+    }
+}
@@ -0,0 +1,321 @@
+use core::{fmt, ops};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+use core::iter::{IntoIterator, FromIterator, Extend};
+use alloc::collections::BTreeMap;
+use alloc::collections::btree_map::{self, Entry};
+
+use super::{Bitmap, Iter};
+#[cfg(feature = "std")]
+use super::super::repr::io::{read_u32, write_u32, invalid_data};
+
+/// Bitmap of `u64`.
+///
+/// The high 32 bits of each element select a `Bitmap`,
+/// and the low 32 bits are stored in it.
+/// Semantics of each method follows the one of `Bitmap`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Bitmap64 {
+    map: BTreeMap<u32, Bitmap>,
+}
+
+fn split(x: u64) -> (u32, u32) {
+    ((x >> 32) as u32, x as u32)
+}
+
+fn join(key: u32, bit: u32) -> u64 {
+    (key as u64) << 32 | bit as u64
+}
+
+impl Bitmap64 {
+    pub fn new() -> Bitmap64 {
+        Bitmap64::default()
+    }
+
+    /// Count of elements.
+    pub fn ones(&self) -> u64 {
+        self.map.values().fold(0, |acc, bitmap| acc + bitmap.ones())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn optimize(&mut self) {
+        for bitmap in self.map.values_mut() {
+            bitmap.optimize();
+        }
+    }
+
+    pub fn contains(&self, x: u64) -> bool {
+        let (key, bit) = split(x);
+        self.map.get(&key).is_some_and(|bitmap| bitmap.contains(bit))
+    }
+
+    pub fn insert(&mut self, x: u64) -> bool {
+        let (key, bit) = split(x);
+        self.map.entry(key).or_default().insert(bit)
+    }
+
+    pub fn remove(&mut self, x: u64) -> bool {
+        let (key, bit) = split(x);
+        match self.map.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let ok = entry.get_mut().remove(bit);
+                if entry.get().is_empty() {
+                    entry.remove();
+                }
+                ok
+            }
+            Entry::Vacant(_) => false,
+        }
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        let first = self.map.iter().next();
+        first.and_then(|(&key, bitmap)| bitmap.min().map(|bit| join(key, bit)))
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        let last = self.map.iter().next_back();
+        last.and_then(|(&key, bitmap)| bitmap.max().map(|bit| join(key, bit)))
+    }
+
+    /// Count elements less than or equal to `x`.
+    pub fn rank(&self, x: u64) -> u64 {
+        let (key, bit) = split(x);
+        let rank = self.map.range(..key).fold(0, |acc, (_, bitmap)| acc + bitmap.ones());
+        rank + self.map.get(&key).map_or(0, |bitmap| bitmap.rank(bit))
+    }
+
+    /// Return the 'c+1'th smallest element.
+    pub fn select(&self, c: u64) -> Option<u64> {
+        let mut r = c;
+        for (&key, bitmap) in &self.map {
+            let w = bitmap.ones();
+            if r < w {
+                return bitmap.select(r).map(|bit| join(key, bit));
+            }
+            r -= w;
+        }
+        None
+    }
+
+    pub fn iter(&self) -> Iter64<'_> {
+        Iter64 {
+            ones: self.ones(),
+            map: self.map.iter(),
+            head: None,
+        }
+    }
+}
+
+// Layout, all integers in little endian:
+//
+//   len:  u64  count of bitmaps
+//   then `len` times
+//     key:    u32
+//     bitmap: see `Bitmap::write_to`
+
+#[cfg(feature = "std")]
+impl Bitmap64 {
+    /// Serialize this bitmap into `w`.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        // Each key is u32, so there are at most `1 << 32` bitmaps.
+        w.write_all(&(self.map.len() as u64).to_le_bytes())?;
+        for (&key, bitmap) in &self.map {
+            write_u32(w, key)?;
+            bitmap.write_to(w)?;
+        }
+        Ok(())
+    }
+
+    /// Deserialize a bitmap written by `write_to`.
+    pub fn read_from<R: Read>(r: &mut R) -> io::Result<Bitmap64> {
+        let mut buf = [0; 8];
+        r.read_exact(&mut buf)?;
+        let len = u64::from_le_bytes(buf);
+        if len > 1 << 32 {
+            return Err(invalid_data("too many bitmaps"));
+        }
+        let mut map = BTreeMap::new();
+        let mut last = None;
+        for _ in 0..len {
+            let key = read_u32(r)?;
+            if last.is_some_and(|last| last >= key) {
+                return Err(invalid_data("keys are not sorted"));
+            }
+            let bitmap = Bitmap::read_from(r)?;
+            if bitmap.is_empty() {
+                return Err(invalid_data("empty bitmap"));
+            }
+            map.insert(key, bitmap);
+            last = Some(key);
+        }
+        Ok(Bitmap64 { map })
+    }
+}
+
+pub struct Iter64<'a> {
+    ones: u64,
+    map: btree_map::Iter<'a, u32, Bitmap>,
+    head: Option<(u32, Iter<'a>)>,
+}
+
+impl<'a> Iterator for Iter64<'a> {
+    type Item = u64;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, ref mut iter)) = self.head {
+                if let Some(bit) = iter.next() {
+                    self.ones -= 1;
+                    return Some(join(key, bit));
+                }
+            }
+            match self.map.next() {
+                Some((&key, bitmap)) => self.head = Some((key, bitmap.iter())),
+                None => return None,
+            }
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.ones <= usize::MAX as u64 {
+            (self.ones as usize, Some(self.ones as usize))
+        } else {
+            (usize::MAX, None)
+        }
+    }
+}
+
+impl fmt::Debug for Bitmap64 {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt,
+               "Bitmap64{{ones:{:?}, bitmaps:{:?}}}",
+               self.ones(),
+               self.map.len())
+    }
+}
+
+impl<'a, 'b> ops::BitAnd<&'b Bitmap64> for &'a Bitmap64 {
+    type Output = Bitmap64;
+    fn bitand(self, that: &Bitmap64) -> Self::Output {
+        let mut map = BTreeMap::new();
+        for (&key, bitmap) in &self.map {
+            if let Some(other) = that.map.get(&key) {
+                let bitmap = bitmap & other;
+                if !bitmap.is_empty() {
+                    map.insert(key, bitmap);
+                }
+            }
+        }
+        Bitmap64 { map }
+    }
+}
+impl<'a> ops::BitAndAssign<&'a Bitmap64> for Bitmap64 {
+    fn bitand_assign(&mut self, that: &Bitmap64) {
+        self.map.retain(|key, bitmap| match that.map.get(key) {
+            Some(other) => {
+                *bitmap &= other;
+                !bitmap.is_empty()
+            }
+            None => false,
+        });
+    }
+}
+
+impl<'a, 'b> ops::BitOr<&'b Bitmap64> for &'a Bitmap64 {
+    type Output = Bitmap64;
+    fn bitor(self, that: &Bitmap64) -> Self::Output {
+        let mut clone = self.clone();
+        clone |= that;
+        clone
+    }
+}
+impl<'a> ops::BitOrAssign<&'a Bitmap64> for Bitmap64 {
+    fn bitor_assign(&mut self, that: &Bitmap64) {
+        for (&key, other) in &that.map {
+            match self.map.entry(key) {
+                Entry::Occupied(mut entry) => *entry.get_mut() |= other,
+                Entry::Vacant(entry) => {
+                    entry.insert(other.clone());
+                }
+            }
+        }
+    }
+}
+
+impl<'a, 'b> ops::BitXor<&'b Bitmap64> for &'a Bitmap64 {
+    type Output = Bitmap64;
+    fn bitxor(self, that: &Bitmap64) -> Self::Output {
+        let mut clone = self.clone();
+        clone ^= that;
+        clone
+    }
+}
+impl<'a> ops::BitXorAssign<&'a Bitmap64> for Bitmap64 {
+    fn bitxor_assign(&mut self, that: &Bitmap64) {
+        for (&key, other) in &that.map {
+            match self.map.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    *entry.get_mut() ^= other;
+                    if entry.get().is_empty() {
+                        entry.remove();
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(other.clone());
+                }
+            }
+        }
+    }
+}
+
+impl<'a, 'b> ops::Sub<&'b Bitmap64> for &'a Bitmap64 {
+    type Output = Bitmap64;
+    fn sub(self, that: &Bitmap64) -> Self::Output {
+        let mut clone = self.clone();
+        clone -= that;
+        clone
+    }
+}
+impl<'a> ops::SubAssign<&'a Bitmap64> for Bitmap64 {
+    fn sub_assign(&mut self, that: &Bitmap64) {
+        self.map.retain(|key, bitmap| match that.map.get(key) {
+            Some(other) => {
+                *bitmap -= other;
+                !bitmap.is_empty()
+            }
+            None => true,
+        });
+    }
+}
+
+impl FromIterator<u64> for Bitmap64 {
+    fn from_iter<I: IntoIterator<Item = u64>>(iterable: I) -> Self {
+        let mut bitmap = Bitmap64::new();
+        bitmap.extend(iterable);
+        bitmap
+    }
+}
+impl<'a> FromIterator<&'a u64> for Bitmap64 {
+    fn from_iter<I: IntoIterator<Item = &'a u64>>(iterable: I) -> Self {
+        let iter = iterable.into_iter();
+        iter.cloned().collect::<Bitmap64>()
+    }
+}
+
+impl Extend<u64> for Bitmap64 {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, iterable: I) {
+        for x in iterable {
+            self.insert(x);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Bitmap64 {
+    type Item = <Iter64<'a> as Iterator>::Item;
+    type IntoIter = Iter64<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
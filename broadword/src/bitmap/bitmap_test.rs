@@ -0,0 +1,434 @@
+extern crate rand;
+use self::rand::Rng;
+
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "std")]
+use std::io::Cursor;
+
+use super::*;
+
+// Elements are drawn from a few containers so that both Vec and Map show up.
+fn gen32<R: Rng>(size: usize, rng: &mut R) -> (Bitmap, BTreeSet<u32>) {
+    let keys = [0u32, 1, 7, 0xFFFF];
+    let mut bitmap = Bitmap::new();
+    let mut set = BTreeSet::new();
+    for _ in 0..size {
+        let key = *rng.choose(&keys).unwrap();
+        let x = key << 16 | rng.gen::<u16>() as u32;
+        assert_eq!(bitmap.insert(x), set.insert(x));
+    }
+    (bitmap, set)
+}
+
+fn gen64<R: Rng>(size: usize, rng: &mut R) -> (Bitmap64, BTreeSet<u64>) {
+    let keys = [0u64, 3, 0xFFFF_FFFF];
+    let mut bitmap = Bitmap64::new();
+    let mut set = BTreeSet::new();
+    for _ in 0..size {
+        let key = *rng.choose(&keys).unwrap();
+        let x = key << 32 | rng.gen_range(0, 1 << 18);
+        assert_eq!(bitmap.insert(x), set.insert(x));
+    }
+    (bitmap, set)
+}
+
+static SIZES: &[usize] = &[0, 1, 100, Repr::VEC_SIZE * 3, Repr::SIZE];
+
+#[test]
+fn bitmap_insert_remove() {
+    let mut rng = rand::thread_rng();
+    for &size in SIZES {
+        let (mut bitmap, mut set) = gen32(size, &mut rng);
+        assert_eq!(bitmap.ones(), set.len() as u64);
+        assert_eq!(bitmap.min(), set.iter().next().cloned());
+        assert_eq!(bitmap.max(), set.iter().next_back().cloned());
+        assert!(bitmap.iter().eq(set.iter().cloned()));
+
+        for _ in 0..size {
+            let x = rng.gen_range(0, 8 << 16);
+            assert_eq!(bitmap.contains(x), set.contains(&x));
+            assert_eq!(bitmap.remove(x), set.remove(&x));
+        }
+        for x in set.iter().cloned().collect::<Vec<u32>>() {
+            assert!(bitmap.remove(x));
+        }
+        assert!(bitmap.is_empty());
+    }
+}
+
+#[test]
+fn bitmap_rank_select() {
+    let mut rng = rand::thread_rng();
+    for &size in SIZES {
+        let (bitmap, set) = gen32(size, &mut rng);
+        for (i, &x) in set.iter().enumerate() {
+            assert_eq!(bitmap.select(i as u64), Some(x));
+            assert_eq!(bitmap.rank(x), i as u64 + 1);
+        }
+        assert_eq!(bitmap.select(set.len() as u64), None);
+        assert_eq!(bitmap.rank(!0), set.len() as u64);
+
+        for _ in 0..100 {
+            let x = rng.gen();
+            assert_eq!(bitmap.rank(x), set.range(..x + 1).count() as u64);
+        }
+    }
+}
+
+#[test]
+fn bitmap_rank_select_cached() {
+    let mut rng = rand::thread_rng();
+    let (mut bitmap, mut set) = gen32(Repr::VEC_SIZE * 3, &mut rng);
+    bitmap.optimize();
+    for _ in 0..500 {
+        // Mutate a random container, adding and removing some of them.
+        let x = rng.gen_range(0, 8 << 16);
+        if rng.gen() {
+            assert_eq!(bitmap.insert(x), set.insert(x));
+        } else {
+            assert_eq!(bitmap.remove(x), set.remove(&x));
+        }
+        if rng.gen_weighted_bool(10) {
+            bitmap.optimize();
+        }
+        assert_eq!(bitmap.rank.len(), bitmap.keys.len() + 1);
+        for i in 0..bitmap.keys.len() {
+            assert_eq!(bitmap.count(i + 1) - bitmap.count(i), bitmap.reprs[i].ones() as u64);
+        }
+
+        assert_eq!(bitmap.ones(), set.len() as u64);
+        let c = rng.gen_range(0, set.len() + 1);
+        assert_eq!(bitmap.select(c as u64), set.iter().nth(c).cloned());
+        let x = rng.gen_range(0, 8 << 16);
+        assert_eq!(bitmap.rank(x), set.range(..x + 1).count() as u64);
+    }
+}
+
+#[test]
+fn bitmap_iter_at() {
+    let mut rng = rand::thread_rng();
+    for &size in SIZES {
+        let (bitmap, set) = gen32(size, &mut rng);
+        let vec = set.iter().cloned().collect::<Vec<u32>>();
+        for _ in 0..100 {
+            let offset = rng.gen_range(0, size + 2);
+            let limit = rng.gen_range(0, 3000);
+            let page = bitmap.iter_at(offset as u64).take(limit).collect::<Vec<u32>>();
+            let want = vec.iter().skip(offset).take(limit).cloned().collect::<Vec<u32>>();
+            assert_eq!(page, want);
+            assert_eq!(bitmap.iter_at(offset as u64).len(), vec.len().saturating_sub(offset));
+        }
+    }
+}
+
+#[test]
+fn bitmap_bulk_load() {
+    let mut rng = rand::thread_rng();
+    for &size in &[0, 1, 100, Repr::VEC_SIZE * 3, Repr::SIZE * 2] {
+        let mut xs = (0..size).map(|_| rng.gen_range(0, 8 << 16)).collect::<Vec<u32>>();
+        let want = xs.iter().collect::<Bitmap>();
+
+        let unsorted = Bitmap::from_unsorted(&xs);
+        assert_eq!(unsorted, want);
+        assert_eq!(unsorted.ones(), want.ones());
+
+        // Elements out of order are found, not taken as sorted.
+        assert_eq!(Bitmap::from_sorted_slice(&xs), want);
+        let mut extended = Bitmap::from_sorted_slice(&[0, 1 << 20]);
+        extended.extend_sorted(&xs);
+        assert_eq!(extended, &want | &Bitmap::from_sorted_slice(&[0, 1 << 20]));
+
+        xs.sort();
+        let sorted = Bitmap::from_sorted_slice(&xs);
+        assert_eq!(sorted, want);
+        assert_eq!(sorted.ones(), want.ones());
+
+        // Interleave containers, so that some are merged and some are new.
+        let (lhs, rhs): (Vec<u32>, Vec<u32>) = xs.iter().partition(|&&x| x >> 16 < 3);
+        let mut extended = Bitmap::from_sorted_slice(&rhs[..rhs.len() / 2]);
+        extended.extend_sorted(&lhs);
+        extended.extend_sorted(&rhs);
+        assert_eq!(extended, want);
+        for (i, x) in want.iter().enumerate().step_by(97) {
+            assert_eq!(extended.select(i as u64), Some(x));
+            assert_eq!(extended.rank(x), i as u64 + 1);
+        }
+    }
+}
+
+macro_rules! bitops_test {
+    ( $gen: ident, $op: tt, $method: ident ) => {
+        let mut rng = rand::thread_rng();
+        for &x in SIZES {
+            for &y in SIZES {
+                let (lhs, lhs_set) = $gen(x, &mut rng);
+                let (rhs, rhs_set) = $gen(y, &mut rng);
+                let want = lhs_set.$method(&rhs_set).cloned().collect::<Vec<_>>();
+
+                let got = &lhs $op &rhs;
+                assert!(got.iter().eq(want.iter().cloned()));
+                assert_eq!(got.ones(), want.len() as u64);
+
+                let mut assign = lhs.clone();
+                bitops_test!(assign, $op, &rhs);
+                assert_eq!(assign, got);
+            }
+        }
+    };
+    ( $lhs: ident, &, $rhs: expr ) => { $lhs &= $rhs; };
+    ( $lhs: ident, |, $rhs: expr ) => { $lhs |= $rhs; };
+    ( $lhs: ident, ^, $rhs: expr ) => { $lhs ^= $rhs; };
+    ( $lhs: ident, -, $rhs: expr ) => { $lhs -= $rhs; };
+}
+
+#[test]
+fn bitmap_bitops() {
+    bitops_test!(gen32, &, intersection);
+    bitops_test!(gen32, |, union);
+    bitops_test!(gen32, ^, symmetric_difference);
+    bitops_test!(gen32, -, difference);
+}
+
+#[test]
+fn bitmap_threshold() {
+    let mut rng = rand::thread_rng();
+    let pairs = SIZES.iter().map(|&size| gen32(size, &mut rng)).collect::<Vec<_>>();
+    let mut counts = BTreeMap::new();
+    for &(_, ref set) in &pairs {
+        for &x in set {
+            *counts.entry(x).or_insert(0) += 1;
+        }
+    }
+    let bitmaps = pairs.iter().map(|&(ref bitmap, _)| bitmap).collect::<Vec<&Bitmap>>();
+    for k in 1..bitmaps.len() + 2 {
+        let got = Bitmap::threshold(&bitmaps, k);
+        let want = counts.iter().filter(|&(_, &c)| c >= k).map(|(&x, _)| x);
+        assert!(got.iter().eq(want));
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn bitmap_par_bitops() {
+    // Enough containers to be split across threads.
+    let mut rng = rand::thread_rng();
+    let mut gen = |size: usize, keys: u32| {
+        let xs = (0..size).map(|_| rng.gen_range(0, keys << 16)).collect::<Vec<u32>>();
+        Bitmap::from_unsorted(&xs)
+    };
+    for &(x, y) in &[(0, 1000), (100, 100000), (200000, 100000), (500000, 5000)] {
+        let lhs = gen(x, 1000);
+        let rhs = gen(y, 700);
+        let more = gen(y, 2000);
+        assert_eq!(lhs.par_and(&rhs), &lhs & &rhs);
+        assert_eq!(lhs.par_or(&rhs), &lhs | &rhs);
+        assert_eq!(lhs.par_xor(&rhs), &lhs ^ &rhs);
+        assert_eq!(lhs.par_andnot(&rhs), &lhs - &rhs);
+        assert_eq!(rhs.par_andnot(&lhs), &rhs - &lhs);
+        assert_eq!(lhs.par_and_ones(&rhs), (&lhs & &rhs).ones());
+
+        let union = Bitmap::par_union_many(&[&lhs, &rhs, &more]);
+        assert_eq!(union, &(&lhs | &rhs) | &more);
+        assert_eq!(union.ones(), union.iter().count() as u64);
+    }
+    assert_eq!(Bitmap::par_union_many(&[]), Bitmap::new());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn bitmap_read_write() {
+    let mut rng = rand::thread_rng();
+    for &size in SIZES {
+        let (mut bitmap, _) = gen32(size, &mut rng);
+        for _ in 0..2 {
+            let mut buf = Vec::new();
+            bitmap.write_to(&mut buf).unwrap();
+            let read = Bitmap::read_from(&mut Cursor::new(&buf)).unwrap();
+            assert_eq!(read, bitmap);
+
+            // Truncated input must not be accepted.
+            if !buf.is_empty() {
+                let len = buf.len() - 1;
+                assert!(Bitmap::read_from(&mut Cursor::new(&buf[..len])).is_err());
+            }
+            bitmap.optimize();
+        }
+    }
+
+    // Unsorted Vec container.
+    let buf = [1, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 3, 0, 1, 0];
+    assert!(Bitmap::read_from(&mut Cursor::new(&buf[..])).is_err());
+}
+
+#[test]
+fn concurrent_bitmap_insert() {
+    let mut rng = rand::thread_rng();
+    let bitmap = ConcurrentBitmap::new();
+    // Threads insert overlapping elements, both into sparse containers
+    // and into ones which move to atomic words on the way.
+    let mut inputs = Vec::new();
+    for _ in 0..4 {
+        let mut xs = (0..Repr::VEC_SIZE * 8).map(|_| rng.gen_range(0, 2 << 16)).collect::<Vec<u32>>();
+        xs.extend((0..1000).map(|_| rng.gen::<u32>()));
+        inputs.push(xs);
+    }
+    let inserted = std::thread::scope(|scope| {
+        let bitmap = &bitmap;
+        let handles = inputs.iter()
+            .map(|xs| scope.spawn(move || xs.iter().filter(|&&x| bitmap.insert(x)).count()))
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|handle| handle.join().unwrap()).sum::<usize>()
+    });
+
+    let set = inputs.iter().flatten().cloned().collect::<BTreeSet<u32>>();
+    assert_eq!(inserted, set.len());
+    assert_eq!(bitmap.ones(), set.len() as u64);
+    let snapshot = bitmap.snapshot();
+    assert!(snapshot.iter().eq(set.iter().cloned()));
+    assert_eq!(snapshot.ones(), set.len() as u64);
+    assert_eq!(snapshot, set.iter().collect::<Bitmap>());
+
+    for &x in set.iter().take(100) {
+        assert!(bitmap.contains(x));
+        assert!(bitmap.remove(x));
+        assert!(!bitmap.contains(x));
+        assert!(!bitmap.remove(x));
+    }
+    assert_eq!(bitmap.snapshot().ones(), set.len() as u64 - 100);
+}
+
+#[test]
+fn bitmap64_insert_remove() {
+    let mut rng = rand::thread_rng();
+    for &size in SIZES {
+        let (mut bitmap, mut set) = gen64(size, &mut rng);
+        assert_eq!(bitmap.ones(), set.len() as u64);
+        assert_eq!(bitmap.min(), set.iter().next().cloned());
+        assert_eq!(bitmap.max(), set.iter().next_back().cloned());
+        assert!(bitmap.iter().eq(set.iter().cloned()));
+
+        for x in set.iter().cloned().collect::<Vec<u64>>() {
+            assert!(bitmap.contains(x));
+            assert!(bitmap.remove(x));
+            assert!(set.remove(&x));
+            assert!(!bitmap.contains(x));
+        }
+        assert!(bitmap.is_empty());
+    }
+}
+
+#[test]
+fn bitmap64_rank_select() {
+    let mut rng = rand::thread_rng();
+    for &size in SIZES {
+        let (bitmap, set) = gen64(size, &mut rng);
+        for (i, &x) in set.iter().enumerate() {
+            assert_eq!(bitmap.select(i as u64), Some(x));
+            assert_eq!(bitmap.rank(x), i as u64 + 1);
+        }
+        assert_eq!(bitmap.select(set.len() as u64), None);
+        assert_eq!(bitmap.rank(!0), set.len() as u64);
+    }
+}
+
+#[test]
+fn bitmap64_bitops() {
+    bitops_test!(gen64, &, intersection);
+    bitops_test!(gen64, |, union);
+    bitops_test!(gen64, ^, symmetric_difference);
+    bitops_test!(gen64, -, difference);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn bitmap64_read_write() {
+    let mut rng = rand::thread_rng();
+    for &size in SIZES {
+        let (bitmap, _) = gen64(size, &mut rng);
+        let mut buf = Vec::new();
+        bitmap.write_to(&mut buf).unwrap();
+        let read = Bitmap64::read_from(&mut Cursor::new(&buf)).unwrap();
+        assert_eq!(read, bitmap);
+    }
+}
+
+fn gen_sparse<R: Rng>(size: usize, rng: &mut R) -> (SparseArray<u64>, BTreeMap<u32, u64>) {
+    let keys = [0u32, 1, 7, 0xFFFF];
+    let mut array = SparseArray::new();
+    let mut map = BTreeMap::new();
+    for _ in 0..size {
+        let key = *rng.choose(&keys).unwrap();
+        let x = key << 16 | rng.gen::<u16>() as u32;
+        let value = rng.gen::<u64>();
+        assert_eq!(array.insert(x, value), map.insert(x, value));
+    }
+    (array, map)
+}
+
+#[test]
+fn sparse_array_insert_remove() {
+    let mut rng = rand::thread_rng();
+    for &size in SIZES {
+        let (mut array, mut map) = gen_sparse(size, &mut rng);
+        assert_eq!(array.len(), map.len());
+        assert!(array.iter().eq(map.iter().map(|(&k, v)| (k, v))));
+        assert_eq!(array.keys().ones(), map.len() as u64);
+
+        for _ in 0..size {
+            let x = rng.gen_range(0, 8 << 16);
+            assert_eq!(array.get(x), map.get(&x));
+            if let Some(value) = array.get_mut(x) {
+                *value += 1;
+                *map.get_mut(&x).unwrap() += 1;
+            }
+            if rng.gen_weighted_bool(2) {
+                assert_eq!(array.remove(x), map.remove(&x));
+            }
+        }
+        for (&x, value) in &map {
+            assert_eq!(array.get(x), Some(value));
+        }
+        for x in map.keys().cloned().collect::<Vec<u32>>() {
+            assert_eq!(array.remove(x), map.remove(&x));
+            assert!(!array.contains_key(x));
+        }
+        assert!(array.is_empty());
+    }
+}
+
+#[test]
+fn sparse_array_merge() {
+    let mut rng = rand::thread_rng();
+    for &size in SIZES {
+        let (a, map_a) = gen_sparse(size, &mut rng);
+        let (b, map_b) = gen_sparse(size, &mut rng);
+
+        let mut merged = a.clone();
+        merged.merge(b.clone());
+        let mut map = map_a.clone();
+        map.extend(map_b.iter().map(|(&k, &v)| (k, v)));
+        assert!(merged.iter().eq(map.iter().map(|(&k, v)| (k, v))));
+
+        let mut summed = a.clone();
+        summed.merge_with(b.clone(), |x, y| *x = x.wrapping_add(y));
+        for (x, &value) in &summed {
+            let expected = match (map_a.get(&x), map_b.get(&x)) {
+                (Some(a), Some(b)) => a.wrapping_add(*b),
+                (Some(a), None) => *a,
+                (None, b) => *b.unwrap(),
+            };
+            assert_eq!(value, expected);
+        }
+        assert_eq!(summed.len(), map.len());
+
+        let collected = map.iter().map(|(&k, &v)| (k, v)).collect::<SparseArray<u64>>();
+        assert_eq!(collected, merged);
+
+        // Unsorted, with each key of `b` first given a value to be replaced.
+        let mut pairs = map_b.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>();
+        rng.shuffle(&mut pairs);
+        let mut extended = a.clone();
+        extended.extend(pairs.iter().map(|&(k, _)| (k, 0)).chain(pairs.iter().cloned()));
+        assert_eq!(extended, merged);
+    }
+}
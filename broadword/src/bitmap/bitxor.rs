@@ -0,0 +1,62 @@
+use core::ops;
+use core::cmp::Ordering::{Less, Equal, Greater};
+use super::Bitmap;
+
+impl Bitmap {
+    fn symmetric_difference_with(&mut self, that: &Bitmap) {
+        let (keys, reprs) = self.take();
+
+        let mut j = 0;
+        for (key, mut repr) in keys.into_iter().zip(reprs) {
+            while j < that.keys.len() && that.keys[j] < key {
+                self.push(that.keys[j], that.reprs[j].clone());
+                j += 1;
+            }
+            if j < that.keys.len() && that.keys[j] == key {
+                repr ^= &that.reprs[j];
+                j += 1;
+            }
+            self.push(key, repr);
+        }
+        for (&key, repr) in that.keys[j..].iter().zip(&that.reprs[j..]) {
+            self.push(key, repr.clone());
+        }
+    }
+}
+
+impl<'a, 'b> ops::BitXor<&'b Bitmap> for &'a Bitmap {
+    type Output = Bitmap;
+    fn bitxor(self, that: &Bitmap) -> Self::Output {
+        let mut bitmap = Bitmap::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.keys.len() && j < that.keys.len() {
+            match self.keys[i].cmp(&that.keys[j]) {
+                Less => {
+                    bitmap.push(self.keys[i], self.reprs[i].clone());
+                    i += 1;
+                }
+                Greater => {
+                    bitmap.push(that.keys[j], that.reprs[j].clone());
+                    j += 1;
+                }
+                Equal => {
+                    bitmap.push(self.keys[i], &self.reprs[i] ^ &that.reprs[j]);
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        for (&key, repr) in self.keys[i..].iter().zip(&self.reprs[i..]) {
+            bitmap.push(key, repr.clone());
+        }
+        for (&key, repr) in that.keys[j..].iter().zip(&that.reprs[j..]) {
+            bitmap.push(key, repr.clone());
+        }
+        bitmap
+    }
+}
+impl<'a> ops::BitXorAssign<&'a Bitmap> for Bitmap {
+    fn bitxor_assign(&mut self, that: &Bitmap) {
+        self.symmetric_difference_with(that);
+    }
+}
@@ -0,0 +1,105 @@
+use alloc::vec::Vec;
+use super::{split, Bitmap, Repr};
+
+impl Bitmap {
+    /// Build from ascending elements, duplicates are ignored.
+    /// Elements out of order are checked for, and built as by `from_unsorted`.
+    pub fn from_sorted_slice(xs: &[u32]) -> Bitmap {
+        if !xs.is_sorted() {
+            return Bitmap::from_unsorted(xs);
+        }
+        let mut bitmap = Bitmap::new();
+        let mut bits = Vec::new();
+        for (key, group) in groups(xs) {
+            bits.clear();
+            bits.extend(group.iter().map(|&x| split(x).1));
+            bitmap.push(key, Repr::from_sorted_slice(&bits));
+        }
+        bitmap
+    }
+
+    /// Build from elements in any order, duplicates are ignored.
+    pub fn from_unsorted(xs: &[u32]) -> Bitmap {
+        // Bucket the low bits by key, a counting sort on the high 16 bits.
+        let mut offsets = vec![0usize; (1 << 16) + 1];
+        for &x in xs {
+            offsets[split(x).0 as usize + 1] += 1;
+        }
+        for key in 0..1 << 16 {
+            offsets[key + 1] += offsets[key];
+        }
+        let mut bits = vec![0u16; xs.len()];
+        {
+            let mut next = offsets.clone();
+            for &x in xs {
+                let (key, bit) = split(x);
+                bits[next[key as usize]] = bit;
+                next[key as usize] += 1;
+            }
+        }
+
+        let mut bitmap = Bitmap::new();
+        for key in 0..1 << 16 {
+            let bucket = &bits[offsets[key]..offsets[key + 1]];
+            if !bucket.is_empty() {
+                bitmap.push(key as u16, Repr::from_unsorted(bucket));
+            }
+        }
+        bitmap
+    }
+
+    /// Insert ascending elements, duplicates are ignored.
+    /// Elements out of order are checked for, and sorted first.
+    pub fn extend_sorted(&mut self, xs: &[u32]) {
+        if !xs.is_sorted() {
+            let mut sorted = xs.to_vec();
+            sorted.sort_unstable();
+            return self.extend_sorted(&sorted);
+        }
+        let (keys, reprs) = self.take();
+        let mut olds = keys.into_iter().zip(reprs).peekable();
+        let mut bits = Vec::new();
+        for (key, group) in groups(xs) {
+            while olds.peek().is_some_and(|&(k, _)| k < key) {
+                let (k, repr) = olds.next().unwrap();
+                self.push(k, repr);
+            }
+            bits.clear();
+            bits.extend(group.iter().map(|&x| split(x).1));
+            let repr = match olds.peek() {
+                Some(&(k, _)) if k == key => {
+                    let (_, mut repr) = olds.next().unwrap();
+                    repr.extend_sorted(&bits);
+                    repr
+                }
+                _ => Repr::from_sorted_slice(&bits),
+            };
+            self.push(key, repr);
+        }
+        for (k, repr) in olds {
+            self.push(k, repr);
+        }
+    }
+}
+
+// Split ascending elements into runs sharing the same high 16 bits.
+// REQUIRES: xs is sorted
+fn groups(xs: &[u32]) -> Groups<'_> {
+    debug_assert!(xs.is_sorted());
+    Groups { xs }
+}
+
+struct Groups<'a> {
+    xs: &'a [u32],
+}
+
+impl<'a> Iterator for Groups<'a> {
+    type Item = (u16, &'a [u32]);
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = split(*self.xs.first()?).0;
+        let len = self.xs.iter().position(|&x| split(x).0 != key).unwrap_or(self.xs.len());
+        let (group, rest) = self.xs.split_at(len);
+        self.xs = rest;
+        Some((key, group))
+    }
+}
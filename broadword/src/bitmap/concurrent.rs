@@ -0,0 +1,255 @@
+use core::{fmt, hint, ptr};
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64};
+use core::sync::atomic::Ordering::{Acquire, AcqRel, Relaxed, Release};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::{split, Bits, Bitmap, Repr};
+
+const WORDS: usize = Repr::SIZE / Repr::BITS_SIZE;
+
+// The 2^16 containers are reached through a two level directory,
+// indexed by the high and the low 8 bits of the key.
+const FANOUT: usize = 1 << 8;
+
+// Bits of a dense container, as in `Repr::Map`.
+struct Block([AtomicU64; WORDS]);
+
+// Sorted bits behind a lock, as in `Repr::Vec`,
+// until there are more than `Repr::VEC_SIZE` of them and they move to `dense` for good.
+struct Container {
+    lock: AtomicBool,
+    sparse: UnsafeCell<Vec<u16>>,
+    dense: AtomicPtr<Block>,
+}
+
+struct Dir([AtomicPtr<Container>; FANOUT]);
+
+/// Bitmap of `u32` which can be updated through a shared reference.
+///
+/// Each container starts as sorted bits behind a spin lock, allocated on the first insert
+/// into it, and becomes an array of atomic words, updated without locking,
+/// once it has more than `Repr::VEC_SIZE` elements.
+/// Containers are never freed until the bitmap is dropped.
+pub struct ConcurrentBitmap {
+    dirs: [AtomicPtr<Dir>; FANOUT],
+}
+
+impl Block {
+    fn new() -> Box<Block> {
+        Box::new(Block(core::array::from_fn(|_| AtomicU64::new(0))))
+    }
+}
+
+impl Container {
+    fn new() -> Box<Container> {
+        let sparse = UnsafeCell::new(Vec::new());
+        Box::new(Container { lock: AtomicBool::new(false), sparse, dense: AtomicPtr::default() })
+    }
+
+    // Run `sparse` on the sorted bits under the lock, or `dense` on the words if moved.
+    fn with<R, S, D>(&self, sparse: S, dense: D) -> R
+        where S: FnOnce(&mut Vec<u16>) -> R,
+              D: FnOnce(&Block) -> R
+    {
+        if let Some(block) = get(&self.dense) {
+            return dense(block);
+        }
+        while self.lock.compare_exchange_weak(false, true, Acquire, Relaxed).is_err() {
+            hint::spin_loop();
+        }
+        // Bits move under the lock, so either they have moved by now or they stay.
+        let r = match get(&self.dense) {
+            Some(block) => dense(block),
+            None => sparse(unsafe { &mut *self.sparse.get() }),
+        };
+        self.lock.store(false, Release);
+        r
+    }
+
+    fn contains(&self, bit: u16) -> bool {
+        let (i, mask) = bitmask(bit);
+        self.with(|bits| bits.binary_search(&bit).is_ok(),
+                  |block| block.0[i].load(Relaxed) & mask != 0)
+    }
+
+    fn insert(&self, bit: u16) -> bool {
+        let (i, mask) = bitmask(bit);
+        let sparse = |bits: &mut Vec<u16>| {
+            let k = match bits.binary_search(&bit) {
+                Ok(_) => return false,
+                Err(k) => k,
+            };
+            bits.insert(k, bit);
+            if bits.len() > Repr::VEC_SIZE {
+                let mut block = Block::new();
+                for &bit in bits.iter() {
+                    let (i, mask) = bitmask(bit);
+                    *block.0[i].get_mut() |= mask;
+                }
+                self.dense.store(Box::into_raw(block), Release);
+                *bits = Vec::new();
+            }
+            true
+        };
+        // Elements are independent of each other, no ordering is needed beyond the word.
+        self.with(sparse, |block| block.0[i].fetch_or(mask, Relaxed) & mask == 0)
+    }
+
+    fn remove(&self, bit: u16) -> bool {
+        let (i, mask) = bitmask(bit);
+        let sparse = |bits: &mut Vec<u16>| match bits.binary_search(&bit) {
+            Ok(k) => {
+                bits.remove(k);
+                true
+            }
+            Err(_) => false,
+        };
+        self.with(sparse, |block| block.0[i].fetch_and(!mask, Relaxed) & mask != 0)
+    }
+
+    fn ones(&self) -> u64 {
+        self.with(|bits| bits.len() as u64,
+                  |block| block.0.iter().fold(0, |acc, w| acc + w.load(Relaxed).ones() as u64))
+    }
+
+    fn repr(&self, words: &mut Vec<u64>) -> Repr {
+        self.with(|bits| Repr::from_sorted_slice(bits), |block| {
+            words.clear();
+            words.extend(block.0.iter().map(|w| w.load(Relaxed)));
+            Repr::from_words(words)
+        })
+    }
+}
+
+impl Drop for Container {
+    fn drop(&mut self) {
+        let block = *self.dense.get_mut();
+        if !block.is_null() {
+            drop(unsafe { Box::from_raw(block) });
+        }
+    }
+}
+
+impl Dir {
+    fn new() -> Box<Dir> {
+        Box::new(Dir(core::array::from_fn(|_| AtomicPtr::new(ptr::null_mut()))))
+    }
+}
+
+// Return the value in `slot`, installing a new one if it is empty.
+fn get_or_alloc<T>(slot: &AtomicPtr<T>, alloc: fn() -> Box<T>) -> &T {
+    let mut p = slot.load(Acquire);
+    if p.is_null() {
+        let new = Box::into_raw(alloc());
+        p = match slot.compare_exchange(ptr::null_mut(), new, AcqRel, Acquire) {
+            Ok(_) => new,
+            Err(installed) => {
+                // Another thread won, drop ours.
+                drop(unsafe { Box::from_raw(new) });
+                installed
+            }
+        };
+    }
+    // Installed pointers are valid until `self` is dropped.
+    unsafe { &*p }
+}
+
+fn get<T>(slot: &AtomicPtr<T>) -> Option<&T> {
+    let p = slot.load(Acquire);
+    if p.is_null() { None } else { Some(unsafe { &*p }) }
+}
+
+fn bitmask(bit: u16) -> (usize, u64) {
+    let bit = bit as usize;
+    (bit / Repr::BITS_SIZE, 1 << (bit % Repr::BITS_SIZE))
+}
+
+impl Default for ConcurrentBitmap {
+    fn default() -> ConcurrentBitmap {
+        let dirs = core::array::from_fn(|_| AtomicPtr::new(ptr::null_mut()));
+        ConcurrentBitmap { dirs }
+    }
+}
+
+impl ConcurrentBitmap {
+    pub fn new() -> ConcurrentBitmap {
+        ConcurrentBitmap::default()
+    }
+
+    fn container(&self, key: u16) -> Option<&Container> {
+        let dir = get(&self.dirs[key as usize / FANOUT])?;
+        get(&dir.0[key as usize % FANOUT])
+    }
+
+    fn container_or_alloc(&self, key: u16) -> &Container {
+        let dir = get_or_alloc(&self.dirs[key as usize / FANOUT], Dir::new);
+        get_or_alloc(&dir.0[key as usize % FANOUT], Container::new)
+    }
+
+    pub fn contains(&self, x: u32) -> bool {
+        let (key, bit) = split(x);
+        self.container(key).is_some_and(|container| container.contains(bit))
+    }
+
+    /// Return true if `x` was not in this bitmap.
+    /// Among threads inserting the same element, exactly one sees true.
+    pub fn insert(&self, x: u32) -> bool {
+        let (key, bit) = split(x);
+        self.container_or_alloc(key).insert(bit)
+    }
+
+    pub fn remove(&self, x: u32) -> bool {
+        let (key, bit) = split(x);
+        self.container(key).is_some_and(|container| container.remove(bit))
+    }
+
+    /// Count of elements, scanning every allocated container.
+    pub fn ones(&self) -> u64 {
+        let mut ones = 0;
+        for key in 0..Repr::SIZE {
+            if let Some(container) = self.container(key as u16) {
+                ones += container.ones();
+            }
+        }
+        ones
+    }
+
+    /// Copy into a `Bitmap`.
+    /// Each word is read atomically, but concurrent updates to other words may or may not be seen.
+    pub fn snapshot(&self) -> Bitmap {
+        let mut bitmap = Bitmap::new();
+        let mut words = Vec::with_capacity(WORDS);
+        for key in 0..Repr::SIZE {
+            if let Some(container) = self.container(key as u16) {
+                bitmap.push(key as u16, container.repr(&mut words));
+            }
+        }
+        bitmap
+    }
+}
+
+impl Drop for ConcurrentBitmap {
+    fn drop(&mut self) {
+        for dir in &mut self.dirs {
+            let dir = *dir.get_mut();
+            if dir.is_null() {
+                continue;
+            }
+            let mut dir = unsafe { Box::from_raw(dir) };
+            for container in &mut dir.0 {
+                let container = *container.get_mut();
+                if !container.is_null() {
+                    drop(unsafe { Box::from_raw(container) });
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ConcurrentBitmap {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "ConcurrentBitmap{{ones:{:?}}}", self.ones())
+    }
+}
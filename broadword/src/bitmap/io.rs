@@ -0,0 +1,45 @@
+use std::io::{self, Read, Write};
+use super::{Bitmap, Bits, Repr};
+use super::super::repr::io::{read_u32, write_u32, invalid_data};
+
+// Layout, all integers in little endian:
+//
+//   len:  u32  count of containers
+//   then `len` times
+//     key:  u16
+//     repr: see `Repr::write_to`
+
+impl Bitmap {
+    /// Serialize this bitmap into `w`.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_u32(w, self.keys.len() as u32)?;
+        for (&key, repr) in self.keys.iter().zip(&self.reprs) {
+            w.write_all(&key.to_le_bytes())?;
+            repr.write_to(w)?;
+        }
+        Ok(())
+    }
+
+    /// Deserialize a bitmap written by `write_to`.
+    pub fn read_from<R: Read>(r: &mut R) -> io::Result<Bitmap> {
+        let len = read_u32(r)? as usize;
+        if len > 1 << 16 {
+            return Err(invalid_data("too many containers"));
+        }
+        let mut bitmap = Bitmap::new();
+        let mut buf = [0; 2];
+        for _ in 0..len {
+            r.read_exact(&mut buf)?;
+            let key = u16::from_le_bytes(buf);
+            if bitmap.keys.last().is_some_and(|&last| last >= key) {
+                return Err(invalid_data("keys are not sorted"));
+            }
+            let repr = Repr::read_from(r)?;
+            if repr.ones() == 0 {
+                return Err(invalid_data("empty container"));
+            }
+            bitmap.push(key, repr);
+        }
+        Ok(bitmap)
+    }
+}
@@ -0,0 +1,49 @@
+use core::iter::{Iterator, ExactSizeIterator};
+use core::slice::Iter as SliceIter;
+
+use super::{join, Repr};
+use super::super::repr::Iter as ReprIter;
+
+pub struct Iter<'a> {
+    ones: usize,
+    keys: SliceIter<'a, u16>,
+    reprs: SliceIter<'a, Repr>,
+    head: Option<(u16, ReprIter<'a>)>,
+}
+
+impl<'a> Iter<'a> {
+    // `ones` is the count of elements in `head` and `reprs`.
+    pub fn new(keys: &'a [u16],
+               reprs: &'a [Repr],
+               head: Option<(u16, ReprIter<'a>)>,
+               ones: u64)
+               -> Iter<'a> {
+        debug_assert_eq!(keys.len(), reprs.len());
+        let ones = ones as usize;
+        let keys = keys.iter();
+        let reprs = reprs.iter();
+        Iter { ones, keys, reprs, head }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u32;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, ref mut iter)) = self.head {
+                if let Some(bit) = iter.next() {
+                    self.ones -= 1;
+                    return Some(join(key, bit));
+                }
+            }
+            match (self.keys.next(), self.reprs.next()) {
+                (Some(&key), Some(repr)) => self.head = Some((key, repr.iter())),
+                _ => return None,
+            }
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.ones, Some(self.ones))
+    }
+}
+impl<'a> ExactSizeIterator for Iter<'a> {}
@@ -0,0 +1,279 @@
+use core::{fmt, mem};
+use alloc::vec::Vec;
+use core::iter::{IntoIterator, FromIterator, Extend};
+
+mod iter;
+pub use self::iter::Iter;
+
+mod bitand;
+mod bitor;
+mod bitxor;
+mod sub;
+mod threshold;
+
+mod rank;
+mod select;
+mod bulk;
+
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+mod par;
+
+mod bitmap64;
+pub use self::bitmap64::Bitmap64;
+
+mod sparse;
+pub use self::sparse::SparseArray;
+
+#[cfg(target_has_atomic = "64")]
+mod concurrent;
+#[cfg(target_has_atomic = "64")]
+pub use self::concurrent::ConcurrentBitmap;
+
+#[cfg(test)]
+mod bitmap_test;
+
+use super::{Bits, Rank, Select1, Repr};
+
+/// Bitmap of `u32`.
+///
+/// The high 16 bits of each element select a container,
+/// and the low 16 bits are stored in that container's `Repr`.
+/// Containers are sorted by key and never empty.
+#[derive(Clone)]
+pub struct Bitmap {
+    keys: Vec<u16>,
+    reprs: Vec<Repr>,
+
+    // Fenwick tree of container cardinalities, `rank[i]` for `i > 0` is the count of
+    // elements in `reprs[i - lowbit(i)..i]`, so that an element is counted in O(log n)
+    // words to update and to sum. Adding or removing a container rebuilds it.
+    rank: Vec<u64>,
+}
+
+fn split(x: u32) -> (u16, u16) {
+    ((x >> 16) as u16, x as u16)
+}
+
+fn join(key: u16, bit: u16) -> u32 {
+    (key as u32) << 16 | bit as u32
+}
+
+// Lowest set bit of a node of `rank`.
+fn lowbit(j: usize) -> usize {
+    j & j.wrapping_neg()
+}
+
+impl Default for Bitmap {
+    fn default() -> Bitmap {
+        let keys = Vec::new();
+        let reprs = Vec::new();
+        let rank = vec![0];
+        Bitmap { keys, reprs, rank }
+    }
+}
+
+impl PartialEq for Bitmap {
+    fn eq(&self, that: &Bitmap) -> bool {
+        self.keys == that.keys && self.reprs == that.reprs
+    }
+}
+impl Eq for Bitmap {}
+
+impl Bitmap {
+    pub fn new() -> Bitmap {
+        Bitmap::default()
+    }
+
+    /// Count of elements.
+    pub fn ones(&self) -> u64 {
+        self.count(self.keys.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Convert each container to more size efficient bits representaions.
+    pub fn optimize(&mut self) {
+        for repr in &mut self.reprs {
+            repr.optimize();
+        }
+    }
+
+    pub fn contains(&self, x: u32) -> bool {
+        let (key, bit) = split(x);
+        match self.keys.binary_search(&key) {
+            Ok(i) => self.reprs[i].contains(bit),
+            Err(_) => false,
+        }
+    }
+
+    pub fn insert(&mut self, x: u32) -> bool {
+        let (key, bit) = split(x);
+        match self.keys.binary_search(&key) {
+            Ok(i) => {
+                let ok = {
+                    let repr = &mut self.reprs[i];
+                    let ok = repr.insert(bit);
+                    // Vec is sorted, keep insertion cheap once it grows large.
+                    if let &mut Repr::Vec(ones, _) = repr {
+                        if ones > Repr::VEC_SIZE {
+                            repr.optimize();
+                        }
+                    }
+                    ok
+                };
+                if ok {
+                    self.patch(i, true);
+                }
+                ok
+            }
+            Err(i) => {
+                let mut repr = Repr::new();
+                repr.insert(bit);
+                self.keys.insert(i, key);
+                self.reprs.insert(i, repr);
+                self.index();
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, x: u32) -> bool {
+        let (key, bit) = split(x);
+        match self.keys.binary_search(&key) {
+            Ok(i) => {
+                if !self.reprs[i].remove(bit) {
+                    return false;
+                }
+                if self.reprs[i].ones() == 0 {
+                    self.keys.remove(i);
+                    self.reprs.remove(i);
+                    self.index();
+                } else {
+                    self.patch(i, false);
+                }
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn min(&self) -> Option<u32> {
+        let first = self.keys.first();
+        first.and_then(|&key| self.reprs[0].iter().next().map(|bit| join(key, bit)))
+    }
+
+    pub fn max(&self) -> Option<u32> {
+        self.keys.last().and_then(|&key| {
+            let repr = &self.reprs[self.reprs.len() - 1];
+            repr.select1(repr.ones() - 1).map(|bit| join(key, bit as u16))
+        })
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(&self.keys, &self.reprs, None, self.ones())
+    }
+
+    /// Iterate elements from the 'c+1'th smallest one,
+    /// `iter_at(offset).take(limit)` is a page of this bitmap.
+    pub fn iter_at(&self, c: u64) -> Iter<'_> {
+        let ones = self.ones();
+        if c >= ones {
+            return Iter::new(&[], &[], None, 0);
+        }
+        let (i, r) = self.search(c);
+        let head = (self.keys[i], self.reprs[i].iter_at(r as usize));
+        Iter::new(&self.keys[i + 1..], &self.reprs[i + 1..], Some(head), ones - c)
+    }
+
+    // Append a container, keys must be pushed in ascending order.
+    fn push(&mut self, key: u16, repr: Repr) {
+        debug_assert!(self.keys.last().is_none_or(|&last| last < key));
+        if repr.ones() > 0 {
+            // The new node sums the nodes under it, and this container.
+            let j = self.keys.len() + 1;
+            let under = self.count(j - 1) - self.count(j - lowbit(j));
+            self.rank.push(under + repr.ones() as u64);
+            self.keys.push(key);
+            self.reprs.push(repr);
+        }
+    }
+
+    // Move out all containers, leave this bitmap empty.
+    fn take(&mut self) -> (Vec<u16>, Vec<Repr>) {
+        self.rank.truncate(1);
+        let keys = mem::take(&mut self.keys);
+        let reprs = mem::take(&mut self.reprs);
+        (keys, reprs)
+    }
+
+    // Count one more or one less element in `reprs[i]`.
+    fn patch(&mut self, i: usize, inserted: bool) {
+        let mut j = i + 1;
+        while j < self.rank.len() {
+            if inserted {
+                self.rank[j] += 1;
+            } else {
+                self.rank[j] -= 1;
+            }
+            j += lowbit(j);
+        }
+    }
+
+    // Rebuild `rank` after adding or removing containers.
+    fn index(&mut self) {
+        let n = self.reprs.len();
+        self.rank.clear();
+        self.rank.push(0);
+        self.rank.extend(self.reprs.iter().map(|repr| repr.ones() as u64));
+        for j in 1..n + 1 {
+            let parent = j + lowbit(j);
+            if parent <= n {
+                self.rank[parent] += self.rank[j];
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Bitmap {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt,
+               "Bitmap{{ones:{:?}, containers:{:?}}}",
+               self.ones(),
+               self.keys.len())
+    }
+}
+
+impl FromIterator<u32> for Bitmap {
+    fn from_iter<I: IntoIterator<Item = u32>>(iterable: I) -> Self {
+        let mut bitmap = Bitmap::new();
+        bitmap.extend(iterable);
+        bitmap
+    }
+}
+impl<'a> FromIterator<&'a u32> for Bitmap {
+    fn from_iter<I: IntoIterator<Item = &'a u32>>(iterable: I) -> Self {
+        let iter = iterable.into_iter();
+        iter.cloned().collect::<Bitmap>()
+    }
+}
+
+impl Extend<u32> for Bitmap {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iterable: I) {
+        // Merge in one pass, rather than inserting each into the rank cache.
+        let mut xs = iterable.into_iter().collect::<Vec<u32>>();
+        xs.sort_unstable();
+        self.extend_sorted(&xs);
+    }
+}
+
+impl<'a> IntoIterator for &'a Bitmap {
+    type Item = <Iter<'a> as Iterator>::Item;
+    type IntoIter = Iter<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
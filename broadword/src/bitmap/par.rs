@@ -0,0 +1,172 @@
+use std::thread;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::cmp::Ordering::{Less, Equal, Greater};
+
+use super::{Bits, Bitmap, Repr};
+
+// Below this count of containers, work is done on the current thread.
+const MIN_JOBS: usize = 64;
+
+// Containers of two bitmaps sharing a key.
+type Job<'a> = (u16, Option<&'a Repr>, Option<&'a Repr>);
+
+/// Operations split by container key over scoped threads.
+/// Results are the same as of the sequential ones.
+impl Bitmap {
+    pub fn par_and(&self, that: &Bitmap) -> Bitmap {
+        let jobs = self.jobs(that, true, false);
+        scatter(&jobs, |&(key, lhs, rhs)| (key, lhs.unwrap() & rhs.unwrap()))
+    }
+
+    pub fn par_or(&self, that: &Bitmap) -> Bitmap {
+        let jobs = self.jobs(that, false, false);
+        scatter(&jobs, |&(key, lhs, rhs)| (key, merge(lhs, rhs, |x, y| x | y)))
+    }
+
+    pub fn par_xor(&self, that: &Bitmap) -> Bitmap {
+        let jobs = self.jobs(that, false, false);
+        scatter(&jobs, |&(key, lhs, rhs)| (key, merge(lhs, rhs, |x, y| x ^ y)))
+    }
+
+    /// Elements of `self` not in `that`.
+    pub fn par_andnot(&self, that: &Bitmap) -> Bitmap {
+        let jobs = self.jobs(that, false, true);
+        scatter(&jobs, |&(key, lhs, rhs)| (key, merge(lhs, rhs, |x, y| x - y)))
+    }
+
+    /// Count of elements in both `self` and `that`, without building the intersection.
+    pub fn par_and_ones(&self, that: &Bitmap) -> u64 {
+        let jobs = self.jobs(that, true, false);
+        let ones = run(&jobs, |chunk| {
+            chunk.iter().fold(0, |acc, &(_, lhs, rhs)| {
+                acc + (lhs.unwrap() & rhs.unwrap()).ones() as u64
+            })
+        });
+        ones.into_iter().sum()
+    }
+
+    /// Union of all `bitmaps`.
+    pub fn par_union_many(bitmaps: &[&Bitmap]) -> Bitmap {
+        let mut all = Vec::new();
+        for bitmap in bitmaps {
+            all.extend(bitmap.keys.iter().cloned().zip(&bitmap.reprs));
+        }
+        all.sort_by_key(|&(key, _)| key);
+
+        // Each job is a run of containers sharing a key.
+        let mut jobs = Vec::new();
+        let mut i = 0;
+        while i < all.len() {
+            let key = all[i].0;
+            let len = all[i..].iter().position(|&(k, _)| k != key).unwrap_or(all.len() - i);
+            jobs.push(&all[i..i + len]);
+            i += len;
+        }
+        scatter(&jobs, |group| {
+            // Start from the largest container, it is likely the densest one.
+            let largest = group.iter().enumerate().max_by_key(|&(_, &(_, repr))| repr.ones());
+            let (j, &(key, largest)) = largest.unwrap();
+            let mut repr = largest.clone();
+            for (k, &(_, other)) in group.iter().enumerate() {
+                if k != j {
+                    repr |= other;
+                }
+            }
+            (key, repr)
+        })
+    }
+
+    // Pair containers by key, keeping only the ones in both if `both`,
+    // or the ones in `self` if `left`.
+    fn jobs<'a>(&'a self, that: &'a Bitmap, both: bool, left: bool) -> Vec<Job<'a>> {
+        let mut jobs = Vec::with_capacity(self.keys.len() + that.keys.len());
+        let (mut i, mut j) = (0, 0);
+        while i < self.keys.len() || j < that.keys.len() {
+            let ord = if i == self.keys.len() {
+                Greater
+            } else if j == that.keys.len() {
+                Less
+            } else {
+                self.keys[i].cmp(&that.keys[j])
+            };
+            match ord {
+                Less => {
+                    if !both {
+                        jobs.push((self.keys[i], Some(&self.reprs[i]), None));
+                    }
+                    i += 1;
+                }
+                Greater => {
+                    if !both && !left {
+                        jobs.push((that.keys[j], None, Some(&that.reprs[j])));
+                    }
+                    j += 1;
+                }
+                Equal => {
+                    jobs.push((self.keys[i], Some(&self.reprs[i]), Some(&that.reprs[j])));
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        jobs
+    }
+}
+
+fn merge<F: Fn(&Repr, &Repr) -> Repr>(lhs: Option<&Repr>, rhs: Option<&Repr>, op: F) -> Repr {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => op(lhs, rhs),
+        (Some(repr), None) | (None, Some(repr)) => repr.clone(),
+        (None, None) => Repr::new(),
+    }
+}
+
+// Build a bitmap from the containers produced by `f` on each job, in order.
+fn scatter<J, F>(jobs: &[J], f: F) -> Bitmap
+    where J: Sync,
+          F: Fn(&J) -> (u16, Repr) + Sync
+{
+    let chunks = run(jobs, |chunk| chunk.iter().map(&f).collect::<Vec<_>>());
+    let mut bitmap = Bitmap::new();
+    for (key, repr) in chunks.into_iter().flatten() {
+        bitmap.push(key, repr);
+    }
+    bitmap
+}
+
+// Split `jobs` into chunks, apply `f` to each on scoped threads, and return results in order.
+fn run<J, T, F>(jobs: &[J], f: F) -> Vec<T>
+    where J: Sync,
+          T: Send,
+          F: Fn(&[J]) -> T + Sync
+{
+    if jobs.len() < MIN_JOBS {
+        return vec![f(jobs)];
+    }
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    // More chunks than threads, so that uneven containers are balanced.
+    let count = (threads * 4).min(jobs.len() / MIN_JOBS * 4).max(1);
+    let size = jobs.len().div_ceil(count);
+    let chunks = jobs.chunks(size).collect::<Vec<_>>();
+
+    let next = AtomicUsize::new(0);
+    let mut done = thread::scope(|scope| {
+        let workers = (0..threads.min(chunks.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        match chunks.get(i) {
+                            Some(chunk) => done.push((i, f(chunk))),
+                            None => return done,
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect::<Vec<_>>()
+    });
+    done.sort_by_key(|&(i, _)| i);
+    done.into_iter().map(|(_, t)| t).collect()
+}
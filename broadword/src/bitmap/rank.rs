@@ -0,0 +1,29 @@
+use super::{split, Bitmap, Rank};
+
+impl Bitmap {
+    /// Count elements less than or equal to `x`.
+    pub fn rank(&self, x: u32) -> u64 {
+        let (key, bit) = split(x);
+        let (i, found) = match self.keys.binary_search(&key) {
+            Ok(i) => (i, true),
+            Err(i) => (i, false),
+        };
+        let rank = self.count(i);
+        if found {
+            let r: usize = self.reprs[i].rank1(bit as usize + 1);
+            rank + r as u64
+        } else {
+            rank
+        }
+    }
+
+    // Count elements in `reprs[..i]`.
+    pub(super) fn count(&self, i: usize) -> u64 {
+        let (mut j, mut count) = (i, 0);
+        while j > 0 {
+            count += self.rank[j];
+            j &= j - 1;
+        }
+        count
+    }
+}
@@ -0,0 +1,31 @@
+use super::{join, Bitmap, Select1};
+
+impl Bitmap {
+    /// Return the 'c+1'th smallest element.
+    pub fn select(&self, c: u64) -> Option<u32> {
+        if c >= self.ones() {
+            return None;
+        }
+        let (i, r) = self.search(c);
+        let repr = &self.reprs[i];
+        repr.select1(r as usize).map(|bit| join(self.keys[i], bit as u16))
+    }
+
+    // Find the container which has the 'c+1'th smallest element,
+    // return its index and the rank of the element in it.
+    // REQUIRES: c < self.ones()
+    pub(super) fn search(&self, c: u64) -> (usize, u64) {
+        // Descend `rank` to the most containers with at most `c` elements in them.
+        let n = self.keys.len();
+        let (mut i, mut r) = (0, c);
+        let mut step = if n == 0 { 0 } else { 1 << (usize::BITS - 1 - n.leading_zeros()) };
+        while step > 0 {
+            if i + step <= n && self.rank[i + step] <= r {
+                i += step;
+                r -= self.rank[i];
+            }
+            step /= 2;
+        }
+        (i, r)
+    }
+}
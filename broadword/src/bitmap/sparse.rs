@@ -0,0 +1,197 @@
+use core::{fmt, mem};
+use core::iter::{IntoIterator, FromIterator, Extend};
+use core::cmp::Ordering::{Less, Equal, Greater};
+use core::slice::Iter as SliceIter;
+use alloc::vec::Vec;
+
+use super::{split, Bitmap, Iter as KeyIter};
+use super::super::Rank;
+
+/// Map of `u32` to values, for keys too sparse for a `Vec`.
+///
+/// Keys are stored in a `Bitmap`, and values in a `Vec` in the order of their keys,
+/// so that the value of a key is at its rank among the keys.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SparseArray<V> {
+    keys: Bitmap,
+    values: Vec<V>,
+}
+
+impl<V> Default for SparseArray<V> {
+    fn default() -> SparseArray<V> {
+        SparseArray { keys: Bitmap::new(), values: Vec::new() }
+    }
+}
+
+impl<V> SparseArray<V> {
+    pub fn new() -> SparseArray<V> {
+        SparseArray::default()
+    }
+
+    /// Count of keys.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn keys(&self) -> &Bitmap {
+        &self.keys
+    }
+
+    /// Values in the order of their keys.
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+
+    // Index of the value of `key`, or where it would be inserted.
+    fn index(&self, key: u32) -> Result<usize, usize> {
+        let (key, bit) = split(key);
+        match self.keys.keys.binary_search(&key) {
+            Ok(i) => {
+                let repr = &self.keys.reprs[i];
+                let index = self.keys.count(i) as usize + repr.rank1(bit as usize);
+                if repr.contains(bit) { Ok(index) } else { Err(index) }
+            }
+            Err(i) => Err(self.keys.count(i) as usize),
+        }
+    }
+
+    pub fn contains_key(&self, key: u32) -> bool {
+        self.keys.contains(key)
+    }
+
+    pub fn get(&self, key: u32) -> Option<&V> {
+        self.index(key).ok().map(|i| &self.values[i])
+    }
+
+    pub fn get_mut(&mut self, key: u32) -> Option<&mut V> {
+        self.index(key).ok().map(move |i| &mut self.values[i])
+    }
+
+    /// Insert `value` at `key`, return the value it replaces.
+    pub fn insert(&mut self, key: u32, value: V) -> Option<V> {
+        match self.index(key) {
+            Ok(i) => Some(mem::replace(&mut self.values[i], value)),
+            Err(i) => {
+                self.keys.insert(key);
+                self.values.insert(i, value);
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: u32) -> Option<V> {
+        self.index(key).ok().map(|i| {
+            self.keys.remove(key);
+            self.values.remove(i)
+        })
+    }
+
+    /// Iterate keys and values in ascending order of keys.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter { keys: self.keys.iter(), values: self.values.iter() }
+    }
+
+    /// Insert all of `that`, values of the keys in both replace those of `self`.
+    pub fn merge(&mut self, that: SparseArray<V>) {
+        self.merge_with(that, |value, other| *value = other);
+    }
+
+    /// Insert all of `that`, calling `f` on the values of the keys in both.
+    pub fn merge_with<F: FnMut(&mut V, V)>(&mut self, that: SparseArray<V>, mut f: F) {
+        let this = mem::take(&mut self.values);
+        let mut values = Vec::with_capacity(this.len() + that.len());
+        {
+            let mut a = self.keys.iter().zip(this).peekable();
+            let mut b = that.keys.iter().zip(that.values).peekable();
+            loop {
+                let order = match (a.peek(), b.peek()) {
+                    (Some(&(x, _)), Some(&(y, _))) => x.cmp(&y),
+                    (Some(_), None) => Less,
+                    (None, Some(_)) => Greater,
+                    (None, None) => break,
+                };
+                match order {
+                    Less => values.push(a.next().unwrap().1),
+                    Greater => values.push(b.next().unwrap().1),
+                    Equal => {
+                        let (mut value, (_, other)) = (a.next().unwrap().1, b.next().unwrap());
+                        f(&mut value, other);
+                        values.push(value);
+                    }
+                }
+            }
+        }
+        self.keys |= &that.keys;
+        self.values = values;
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for SparseArray<V> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_map().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'a, V: 'a> {
+    keys: KeyIter<'a>,
+    values: SliceIter<'a, V>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (u32, &'a V);
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.keys.next(), self.values.next()) {
+            (Some(key), Some(value)) => Some((key, value)),
+            _ => None,
+        }
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+impl<'a, V> ExactSizeIterator for Iter<'a, V> {}
+
+impl<'a, V> IntoIterator for &'a SparseArray<V> {
+    type Item = (u32, &'a V);
+    type IntoIter = Iter<'a, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V> FromIterator<(u32, V)> for SparseArray<V> {
+    fn from_iter<I: IntoIterator<Item = (u32, V)>>(iterable: I) -> Self {
+        let mut array = SparseArray::new();
+        array.extend(iterable);
+        array
+    }
+}
+
+impl<V> Extend<(u32, V)> for SparseArray<V> {
+    /// Later values of a key replace earlier ones, as with `insert`.
+    fn extend<I: IntoIterator<Item = (u32, V)>>(&mut self, iterable: I) {
+        // The sort is stable, so the last value of each key comes last among equal keys.
+        let mut pairs = iterable.into_iter().collect::<Vec<_>>();
+        pairs.sort_by_key(|&(key, _)| key);
+        let mut keys = Vec::with_capacity(pairs.len());
+        let mut values = Vec::with_capacity(pairs.len());
+        for (key, value) in pairs {
+            if keys.last() == Some(&key) {
+                *values.last_mut().unwrap() = value;
+            } else {
+                keys.push(key);
+                values.push(value);
+            }
+        }
+        let that = SparseArray { keys: Bitmap::from_sorted_slice(&keys), values };
+        if self.is_empty() {
+            *self = that;
+        } else {
+            self.merge(that);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use core::ops;
+use core::cmp::Ordering::{Less, Equal, Greater};
+use super::Bitmap;
+
+impl Bitmap {
+    fn difference_with(&mut self, that: &Bitmap) {
+        let (keys, reprs) = self.take();
+
+        let mut j = 0;
+        for (key, mut repr) in keys.into_iter().zip(reprs) {
+            while j < that.keys.len() && that.keys[j] < key {
+                j += 1;
+            }
+            if j < that.keys.len() && that.keys[j] == key {
+                repr -= &that.reprs[j];
+            }
+            self.push(key, repr);
+        }
+    }
+}
+
+impl<'a, 'b> ops::Sub<&'b Bitmap> for &'a Bitmap {
+    type Output = Bitmap;
+    fn sub(self, that: &Bitmap) -> Self::Output {
+        let mut bitmap = Bitmap::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.keys.len() && j < that.keys.len() {
+            match self.keys[i].cmp(&that.keys[j]) {
+                Less => {
+                    bitmap.push(self.keys[i], self.reprs[i].clone());
+                    i += 1;
+                }
+                Greater => j += 1,
+                Equal => {
+                    bitmap.push(self.keys[i], &self.reprs[i] - &that.reprs[j]);
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        for (&key, repr) in self.keys[i..].iter().zip(&self.reprs[i..]) {
+            bitmap.push(key, repr.clone());
+        }
+        bitmap
+    }
+}
+impl<'a> ops::SubAssign<&'a Bitmap> for Bitmap {
+    fn sub_assign(&mut self, that: &Bitmap) {
+        self.difference_with(that);
+    }
+}
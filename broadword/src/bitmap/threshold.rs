@@ -0,0 +1,43 @@
+use core::cmp::Reverse;
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+
+use super::{Bitmap, Repr};
+
+impl Bitmap {
+    /// Elements of at least `k` of `bitmaps`.
+    pub fn threshold(bitmaps: &[&Bitmap], k: usize) -> Bitmap {
+        assert!(k > 0, "threshold must be positive");
+        let mut bitmap = Bitmap::new();
+        if k > bitmaps.len() {
+            return bitmap;
+        }
+        // Merge containers by key, each bitmap at its next container.
+        let mut cursors = vec![0; bitmaps.len()];
+        let mut heap = BinaryHeap::with_capacity(bitmaps.len());
+        for (i, b) in bitmaps.iter().enumerate() {
+            if let Some(&key) = b.keys.first() {
+                heap.push(Reverse((key, i)));
+            }
+        }
+        let mut reprs = Vec::with_capacity(bitmaps.len());
+        while let Some(&Reverse((key, _))) = heap.peek() {
+            reprs.clear();
+            while let Some(&Reverse((same, i))) = heap.peek() {
+                if same != key {
+                    break;
+                }
+                heap.pop();
+                reprs.push(&bitmaps[i].reprs[cursors[i]]);
+                cursors[i] += 1;
+                if let Some(&next) = bitmaps[i].keys.get(cursors[i]) {
+                    heap.push(Reverse((next, i)));
+                }
+            }
+            if reprs.len() >= k {
+                bitmap.push(key, Repr::threshold(&reprs, k));
+            }
+        }
+        bitmap
+    }
+}
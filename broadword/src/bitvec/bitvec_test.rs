@@ -0,0 +1,40 @@
+extern crate rand;
+use self::rand::Rng;
+
+use super::*;
+
+#[test]
+fn bitvec_rank_select() {
+    let mut rng = rand::thread_rng();
+    for &len in &[0, 1, 63, 64, 511, 512, 513, 10_000] {
+        for &density in &[1, 2, 30] {
+            let bits = (0..len).map(|_| rng.gen_weighted_bool(density)).collect::<Vec<bool>>();
+            let bv = bits.iter().cloned().collect::<BitVec>();
+            assert_eq!(bv.len(), len);
+
+            let (mut rank1, mut rank0) = (0, 0);
+            for (i, &b) in bits.iter().enumerate() {
+                assert_eq!(bv.get(i), b);
+                assert_eq!(bv.rank1(i), rank1);
+                assert_eq!(bv.rank0(i), rank0);
+                if b {
+                    assert_eq!(bv.select1(rank1), Some(i));
+                    rank1 += 1;
+                } else {
+                    assert_eq!(bv.select0(rank0), Some(i));
+                    rank0 += 1;
+                }
+            }
+            for i in 0..len.min(200) {
+                let width = rng.gen_range(0, 65.min(len - i + 1));
+                let bits = (0..width).fold(0, |w, k| w | (bv.get(i + k) as u64) << k);
+                assert_eq!(bv.get_bits(i, width), bits);
+            }
+            assert_eq!(bv.ones(), rank1);
+            assert_eq!(bv.zeros(), rank0);
+            assert_eq!(bv.rank1(len + 1), rank1);
+            assert_eq!(bv.select1(rank1), None);
+            assert_eq!(bv.select0(rank0), None);
+        }
+    }
+}
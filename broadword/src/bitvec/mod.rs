@@ -0,0 +1,201 @@
+//! Static bit vector with a rank directory.
+
+use core::iter::FromIterator;
+use alloc::vec::Vec;
+
+use super::{BitLen, Bits, Rank, Select0, Select1};
+
+#[cfg(test)]
+mod bitvec_test;
+
+// Ones are counted per block of a cache line.
+const BLOCK_WORDS: usize = 8;
+const BLOCK_BITS: usize = BLOCK_WORDS * 64;
+
+/// Bit vector of a fixed length and content.
+///
+/// Ones before each block of 512 bits are stored, so that rank takes constant time,
+/// and select takes a binary search over blocks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitVec {
+    len: usize,
+    words: Vec<u64>,
+    // Ones before each block, and in all of them.
+    ranks: Vec<usize>,
+}
+
+impl Default for BitVec {
+    fn default() -> BitVec {
+        BitVec::from_words(&[], 0)
+    }
+}
+
+impl BitVec {
+    /// Build from the first `len` bits of `words`.
+    pub fn from_words(words: &[u64], len: usize) -> BitVec {
+        assert!(len <= words.len() * 64);
+        let blocks = len.div_ceil(BLOCK_BITS);
+        let mut words = words[..len.div_ceil(64)].to_vec();
+        if !len.is_multiple_of(64) {
+            let last = words.len() - 1;
+            words[last] &= (1 << (len % 64)) - 1;
+        }
+        words.resize(blocks * BLOCK_WORDS, 0);
+
+        let mut ranks = Vec::with_capacity(blocks + 1);
+        let mut ones = 0;
+        for block in words.chunks(BLOCK_WORDS) {
+            ranks.push(ones);
+            ones = block.iter().fold(ones, |acc, w| acc + w.ones());
+        }
+        ranks.push(ones);
+        BitVec { len, words, ranks }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Count non-zero bits.
+    pub fn ones(&self) -> usize {
+        self.ranks[self.ranks.len() - 1]
+    }
+
+    /// Count zero bits.
+    pub fn zeros(&self) -> usize {
+        self.len - self.ones()
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        (self.words.len() + self.ranks.len()) * 8
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "index out of bounds");
+        self.words[i / 64] & 1 << (i % 64) != 0
+    }
+
+    /// Read `width` bits from `i`.
+    pub fn get_bits(&self, i: usize, width: usize) -> u64 {
+        assert!(width <= 64 && i + width <= self.len, "index out of bounds");
+        if width == 0 {
+            return 0;
+        }
+        let (q, r) = (i / 64, i % 64);
+        let mut bits = self.words[q] >> r;
+        if r + width > 64 {
+            bits |= self.words[q + 1] << (64 - r);
+        }
+        if width < 64 { bits & ((1 << width) - 1) } else { bits }
+    }
+
+    // Count of ones, or zeros if not `one`, before `block`.
+    fn before(&self, block: usize, one: bool) -> usize {
+        if one { self.ranks[block] } else { block * BLOCK_BITS - self.ranks[block] }
+    }
+
+    fn select(&self, c: usize, one: bool) -> usize {
+        // The last block with at most `c` before it.
+        let (mut lo, mut hi) = (0, self.ranks.len() - 1);
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if self.before(mid, one) <= c {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut c = c - self.before(lo, one);
+        let start = lo * BLOCK_WORDS;
+        for (i, &w) in self.words[start..start + BLOCK_WORDS].iter().enumerate() {
+            let w = if one { w } else { !w };
+            let ones = w.ones();
+            if c < ones {
+                let p: usize = w.select1(c).unwrap();
+                return (start + i) * 64 + p;
+            }
+            c -= ones;
+        }
+        unreachable!("select out of block")
+    }
+}
+
+impl Bits for BitVec {
+    const SIZE: usize = usize::MAX;
+
+    fn none() -> Self {
+        BitVec::default()
+    }
+
+    fn ones(&self) -> usize {
+        BitVec::ones(self)
+    }
+
+    fn zeros(&self) -> usize {
+        BitVec::zeros(self)
+    }
+}
+
+impl BitLen for BitVec {
+    fn len(&self) -> usize {
+        BitVec::len(self)
+    }
+
+    fn ones(&self) -> usize {
+        BitVec::ones(self)
+    }
+
+    fn zeros(&self) -> usize {
+        BitVec::zeros(self)
+    }
+}
+
+impl Rank for BitVec {
+    fn rank1(&self, i: usize) -> usize {
+        if i >= self.len {
+            return self.ones();
+        }
+        let (q, r) = (i / 64, i % 64);
+        let block = i / BLOCK_BITS;
+        let rank: usize = self.words[q].rank1(r);
+        let words = &self.words[block * BLOCK_WORDS..q];
+        words.iter().fold(self.ranks[block] + rank, |acc, w| acc + w.ones())
+    }
+
+    fn rank0(&self, i: usize) -> usize {
+        i.min(self.len) - self.rank1(i)
+    }
+}
+
+impl Select1 for BitVec {
+    fn select1(&self, c: usize) -> Option<usize> {
+        if c < self.ones() { Some(self.select(c, true)) } else { None }
+    }
+}
+
+impl Select0 for BitVec {
+    fn select0(&self, c: usize) -> Option<usize> {
+        if c < self.zeros() { Some(self.select(c, false)) } else { None }
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iterable: I) -> Self {
+        let mut words = Vec::new();
+        let mut len: usize = 0;
+        for bit in iterable {
+            if len.is_multiple_of(64) {
+                words.push(0);
+            }
+            if bit {
+                words[len / 64] |= 1 << (len % 64);
+            }
+            len += 1;
+        }
+        BitVec::from_words(&words, len)
+    }
+}
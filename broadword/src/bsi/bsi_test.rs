@@ -0,0 +1,88 @@
+extern crate rand;
+use self::rand::Rng;
+
+use std::collections::BTreeMap;
+
+use super::*;
+
+fn gen<R: Rng>(size: usize, max: u64, rng: &mut R) -> (BitSlicedIndex, BTreeMap<u32, u64>) {
+    let mut bsi = BitSlicedIndex::new();
+    let mut map = BTreeMap::new();
+    for _ in 0..size {
+        let doc = rng.gen_range(0, 4 << 16);
+        let value = rng.gen_range(0, max);
+        assert_eq!(bsi.insert(doc, value), map.insert(doc, value));
+    }
+    (bsi, map)
+}
+
+fn oracle<F: Fn(u64) -> bool>(map: &BTreeMap<u32, u64>, filter: &Bitmap, p: F) -> Bitmap {
+    let docs = map.iter().filter(|&(&doc, &value)| filter.contains(doc) && p(value));
+    docs.map(|(&doc, _)| doc).collect()
+}
+
+#[test]
+fn bsi_insert_remove() {
+    let mut rng = rand::thread_rng();
+    let (mut bsi, mut map) = gen(5000, 1 << 20, &mut rng);
+    assert_eq!(bsi.len(), map.len() as u64);
+    for _ in 0..5000 {
+        let doc = rng.gen_range(0, 4 << 16);
+        assert_eq!(bsi.get(doc), map.get(&doc).cloned());
+        assert_eq!(bsi.remove(doc), map.remove(&doc));
+    }
+    for doc in map.keys().cloned().collect::<Vec<u32>>() {
+        assert_eq!(bsi.remove(doc), map.remove(&doc));
+    }
+    assert!(bsi.is_empty());
+    assert_eq!(bsi, BitSlicedIndex::new());
+}
+
+#[test]
+fn bsi_compare() {
+    let mut rng = rand::thread_rng();
+    for &max in &[1, 10, 1 << 20, !0] {
+        let (bsi, map) = gen(3000, max, &mut rng);
+        let filter = (0..4 << 16).filter(|_| rng.gen_weighted_bool(3)).collect::<Bitmap>();
+        let values = map.values().cloned().collect::<Vec<u64>>();
+        for _ in 0..20 {
+            let x = if rng.gen() { *rng.choose(&values).unwrap() } else { rng.gen_range(0, max) };
+            let y = if rng.gen() { x.saturating_add(rng.gen_range(0, 1 << 10)) } else { rng.gen() };
+            assert_eq!(bsi.eq(x, &filter), oracle(&map, &filter, |v| v == x));
+            assert_eq!(bsi.lt(x, &filter), oracle(&map, &filter, |v| v < x));
+            assert_eq!(bsi.le(x, &filter), oracle(&map, &filter, |v| v <= x));
+            assert_eq!(bsi.gt(x, &filter), oracle(&map, &filter, |v| v > x));
+            assert_eq!(bsi.ge(x, &filter), oracle(&map, &filter, |v| v >= x));
+            assert_eq!(bsi.between(x, y, &filter), oracle(&map, &filter, |v| x <= v && v <= y));
+        }
+        for &x in &[0, max, !0] {
+            assert_eq!(bsi.lt(x, bsi.docs()), oracle(&map, bsi.docs(), |v| v < x));
+            assert_eq!(bsi.gt(x, bsi.docs()), oracle(&map, bsi.docs(), |v| v > x));
+        }
+    }
+}
+
+#[test]
+fn bsi_aggregate() {
+    let mut rng = rand::thread_rng();
+    for &max in &[1, 10, 1 << 20, !0] {
+        let (bsi, map) = gen(3000, max, &mut rng);
+        for &rate in &[1, 2, 100, 10000] {
+            let filter = (0..4 << 16).filter(|_| rng.gen_weighted_bool(rate)).collect::<Bitmap>();
+            let found = map.iter().filter(|&(doc, _)| filter.contains(*doc)).collect::<Vec<_>>();
+            let values = found.iter().map(|&(_, &value)| value);
+
+            assert_eq!(bsi.sum(&filter), values.clone().fold(0, |acc, v| acc + v as u128));
+            assert_eq!(bsi.min(&filter), values.clone().min());
+            assert_eq!(bsi.max(&filter), values.clone().max());
+
+            for &k in &[0, 1, 10, 100, found.len() as u64, found.len() as u64 + 1] {
+                // Largest values first, then smaller documents first.
+                let mut want = found.clone();
+                want.sort_by(|&(d0, v0), &(d1, v1)| v1.cmp(v0).then(d0.cmp(d1)));
+                let want = want.iter().take(k as usize).map(|&(&doc, _)| doc).collect::<Bitmap>();
+                assert_eq!(bsi.top_k(k, &filter), want);
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Bit-sliced index, as in O'Neil and Quass, "Improved Query Performance with Variant Indexes";
+//! and Rinfret, O'Neil and O'Neil, "Bit-Sliced Index Arithmetic".
+
+use alloc::vec::Vec;
+
+use super::Bitmap;
+
+#[cfg(test)]
+mod bsi_test;
+
+/// Map of `u32` documents to `u64` values.
+///
+/// Bit `i` of each value is stored in the `i`th slice, a `Bitmap` of documents.
+/// Queries take a `filter` of documents to consider, and return a `Bitmap` of documents.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BitSlicedIndex {
+    // Documents which have a value.
+    docs: Bitmap,
+    // `slices[i]` holds documents whose value has bit `i` set.
+    // The last slice is never empty.
+    slices: Vec<Bitmap>,
+}
+
+impl BitSlicedIndex {
+    pub fn new() -> BitSlicedIndex {
+        BitSlicedIndex::default()
+    }
+
+    /// Documents which have a value.
+    pub fn docs(&self) -> &Bitmap {
+        &self.docs
+    }
+
+    /// Count of documents.
+    pub fn len(&self) -> u64 {
+        self.docs.ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    pub fn optimize(&mut self) {
+        self.docs.optimize();
+        for slice in &mut self.slices {
+            slice.optimize();
+        }
+    }
+
+    pub fn get(&self, doc: u32) -> Option<u64> {
+        if !self.docs.contains(doc) {
+            return None;
+        }
+        let value = self.slices
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, slice)| acc | (slice.contains(doc) as u64) << i);
+        Some(value)
+    }
+
+    /// Set the value of `doc`, and return the previous one.
+    pub fn insert(&mut self, doc: u32, value: u64) -> Option<u64> {
+        let old = self.remove(doc);
+        self.docs.insert(doc);
+        let width = 64 - value.leading_zeros() as usize;
+        if self.slices.len() < width {
+            self.slices.resize(width, Bitmap::new());
+        }
+        for (i, slice) in self.slices[..width].iter_mut().enumerate() {
+            if value & 1 << i != 0 {
+                slice.insert(doc);
+            }
+        }
+        old
+    }
+
+    pub fn remove(&mut self, doc: u32) -> Option<u64> {
+        let old = self.get(doc);
+        if old.is_some() {
+            self.docs.remove(doc);
+            for slice in &mut self.slices {
+                slice.remove(doc);
+            }
+            while self.slices.last().is_some_and(|slice| slice.is_empty()) {
+                self.slices.pop();
+            }
+        }
+        old
+    }
+
+    // Split `filter` into documents less than, equal to and greater than `value`.
+    fn compare(&self, value: u64, filter: &Bitmap) -> (Bitmap, Bitmap, Bitmap) {
+        let mut eq = &self.docs & filter;
+        if self.slices.len() < 64 && value >> self.slices.len() != 0 {
+            return (eq, Bitmap::new(), Bitmap::new());
+        }
+        let mut lt = Bitmap::new();
+        let mut gt = Bitmap::new();
+        for (i, slice) in self.slices.iter().enumerate().rev() {
+            if value & 1 << i != 0 {
+                lt |= &(&eq - slice);
+                eq &= slice;
+            } else {
+                gt |= &(&eq & slice);
+                eq -= slice;
+            }
+        }
+        (lt, eq, gt)
+    }
+
+    /// Documents in `filter` whose value is equal to `value`.
+    pub fn eq(&self, value: u64, filter: &Bitmap) -> Bitmap {
+        self.compare(value, filter).1
+    }
+
+    /// Documents in `filter` whose value is less than `value`.
+    pub fn lt(&self, value: u64, filter: &Bitmap) -> Bitmap {
+        self.compare(value, filter).0
+    }
+
+    /// Documents in `filter` whose value is less than or equal to `value`.
+    pub fn le(&self, value: u64, filter: &Bitmap) -> Bitmap {
+        let (lt, eq, _) = self.compare(value, filter);
+        &lt | &eq
+    }
+
+    /// Documents in `filter` whose value is greater than `value`.
+    pub fn gt(&self, value: u64, filter: &Bitmap) -> Bitmap {
+        self.compare(value, filter).2
+    }
+
+    /// Documents in `filter` whose value is greater than or equal to `value`.
+    pub fn ge(&self, value: u64, filter: &Bitmap) -> Bitmap {
+        let (_, eq, gt) = self.compare(value, filter);
+        &gt | &eq
+    }
+
+    /// Documents in `filter` whose value is in `lo..=hi`.
+    pub fn between(&self, lo: u64, hi: u64, filter: &Bitmap) -> Bitmap {
+        if lo > hi {
+            return Bitmap::new();
+        }
+        let ge = self.ge(lo, filter);
+        self.le(hi, &ge)
+    }
+
+    /// Sum of values of documents in `filter`.
+    pub fn sum(&self, filter: &Bitmap) -> u128 {
+        let found = &self.docs & filter;
+        self.slices.iter().enumerate().fold(0, |acc, (i, slice)| {
+            acc + (((slice & &found).ones() as u128) << i)
+        })
+    }
+
+    /// Smallest value of documents in `filter`.
+    pub fn min(&self, filter: &Bitmap) -> Option<u64> {
+        let mut found = &self.docs & filter;
+        if found.is_empty() {
+            return None;
+        }
+        let mut min = 0;
+        for (i, slice) in self.slices.iter().enumerate().rev() {
+            let zeros = &found - slice;
+            if zeros.is_empty() {
+                min |= 1 << i;
+            } else {
+                found = zeros;
+            }
+        }
+        Some(min)
+    }
+
+    /// Largest value of documents in `filter`.
+    pub fn max(&self, filter: &Bitmap) -> Option<u64> {
+        let mut found = &self.docs & filter;
+        if found.is_empty() {
+            return None;
+        }
+        let mut max = 0;
+        for (i, slice) in self.slices.iter().enumerate().rev() {
+            let ones = &found & slice;
+            if !ones.is_empty() {
+                max |= 1 << i;
+                found = ones;
+            }
+        }
+        Some(max)
+    }
+
+    /// `k` documents in `filter` with the largest values.
+    /// Ties are broken by the smaller document.
+    pub fn top_k(&self, k: u64, filter: &Bitmap) -> Bitmap {
+        let mut candidates = &self.docs & filter;
+        if candidates.ones() <= k {
+            return candidates;
+        }
+        // `top` are surely in the result, `candidates` are tied for the rest.
+        let mut top = Bitmap::new();
+        for slice in self.slices.iter().rev() {
+            let ones = &candidates & slice;
+            let with = &top | &ones;
+            let n = with.ones();
+            if n > k {
+                candidates = ones;
+            } else {
+                top = with;
+                candidates -= slice;
+                if n == k {
+                    return top;
+                }
+            }
+        }
+        let rest = k - top.ones();
+        let ties = candidates.iter().take(rest as usize).collect::<Vec<u32>>();
+        top |= &Bitmap::from_sorted_slice(&ties);
+        top
+    }
+}
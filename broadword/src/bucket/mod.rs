@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use super::repr::Repr;
 
 //mod intersection;
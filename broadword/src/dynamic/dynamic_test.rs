@@ -0,0 +1,81 @@
+extern crate rand;
+use self::rand::Rng;
+
+use super::*;
+
+fn check(bv: &DynamicBitVec, bits: &[bool]) {
+    assert_eq!(bv.len(), bits.len());
+    assert_eq!(bv.iter().collect::<Vec<bool>>(), bits);
+    let (mut rank1, mut rank0) = (0, 0);
+    for (i, &b) in bits.iter().enumerate() {
+        assert_eq!(bv.rank1(i), rank1);
+        assert_eq!(bv.rank0(i), rank0);
+        if b {
+            assert_eq!(bv.select1(rank1), Some(i));
+            rank1 += 1;
+        } else {
+            assert_eq!(bv.select0(rank0), Some(i));
+            rank0 += 1;
+        }
+    }
+    assert_eq!(bv.ones(), rank1);
+    assert_eq!(bv.zeros(), rank0);
+    assert_eq!(bv.select1(rank1), None);
+    assert_eq!(bv.select0(rank0), None);
+}
+
+#[test]
+fn dynamic_insert_delete() {
+    let mut rng = rand::thread_rng();
+    let mut bv = DynamicBitVec::new();
+    let mut bits = Vec::new();
+    for round in 0..6 {
+        // Grow, then shrink by turns.
+        let grow = round % 2 == 0;
+        for _ in 0..5000 {
+            let op = rng.gen_range(0, 10);
+            if bits.is_empty() || (grow && op < 6) || (!grow && op < 3) {
+                let i = rng.gen_range(0, bits.len() + 1);
+                let bit = rng.gen_weighted_bool(3);
+                bv.insert_bit(i, bit);
+                bits.insert(i, bit);
+            } else if op < 8 {
+                let i = rng.gen_range(0, bits.len());
+                assert_eq!(bv.delete_bit(i), bits.remove(i));
+            } else {
+                let i = rng.gen_range(0, bits.len());
+                let bit = rng.gen();
+                assert_eq!(bv.set(i, bit), bits[i]);
+                bits[i] = bit;
+            }
+        }
+        check(&bv, &bits);
+        // AVL height bound, counting leaves of at least one bit.
+        let leaves = bits.len().max(1) as f64;
+        assert!((bv.root.height() as f64) <= 1.45 * (leaves + 2.0).log2());
+    }
+    while !bits.is_empty() {
+        let i = rng.gen_range(0, bits.len());
+        assert_eq!(bv.delete_bit(i), bits.remove(i));
+    }
+    assert!(bv.is_empty());
+    assert_eq!(bv.ones(), 0);
+}
+
+#[test]
+fn dynamic_from_iter() {
+    let mut rng = rand::thread_rng();
+    for &len in &[0, 1, 511, 512, 513, 10_000] {
+        let bits = (0..len).map(|_| rng.gen()).collect::<Vec<bool>>();
+        let mut bv = bits.iter().cloned().collect::<DynamicBitVec>();
+        check(&bv, &bits);
+
+        // Appending to full leaves splits them.
+        let mut bits = bits;
+        for i in 0..1000 {
+            bv.push(i % 3 == 0);
+            bits.push(i % 3 == 0);
+        }
+        check(&bv, &bits);
+    }
+}
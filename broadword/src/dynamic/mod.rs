@@ -0,0 +1,506 @@
+//! Bit vector with insertion and deletion at any position.
+//!
+//! Leaves of a few words hang from an AVL tree whose inner nodes
+//! count bits and ones of their subtree, so that every operation is O(log n).
+
+use core::{cmp, fmt, mem};
+use core::iter::FromIterator;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::{BitLen, Bits, Rank, Select0, Select1};
+
+#[cfg(test)]
+mod dynamic_test;
+
+const LEAF_WORDS: usize = 8;
+const LEAF_BITS: usize = LEAF_WORDS * 64;
+
+// Adjacent leaves are merged if they fit in this many bits.
+const MERGE_BITS: usize = LEAF_BITS / 2;
+
+#[derive(Clone, Default)]
+pub struct DynamicBitVec {
+    root: Node,
+}
+
+#[derive(Clone)]
+enum Node {
+    Leaf(Leaf),
+    Inner(Box<Inner>),
+}
+
+#[derive(Clone)]
+struct Inner {
+    left: Node,
+    right: Node,
+    len: usize,
+    ones: usize,
+    height: usize,
+}
+
+#[derive(Clone, Default)]
+struct Leaf {
+    words: [u64; LEAF_WORDS],
+    len: usize,
+    ones: usize,
+}
+
+fn mask(width: usize) -> u64 {
+    if width < 64 { (1 << width) - 1 } else { !0 }
+}
+
+impl Leaf {
+    fn get(&self, i: usize) -> bool {
+        self.words[i / 64] & 1 << (i % 64) != 0
+    }
+
+    fn set(&mut self, i: usize, bit: bool) -> bool {
+        let old = self.get(i);
+        if bit {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+        self.ones = self.ones + bit as usize - old as usize;
+        old
+    }
+
+    // REQUIRES: self.len < LEAF_BITS
+    fn insert(&mut self, i: usize, bit: bool) {
+        let (q, r) = (i / 64, i % 64);
+        for w in (q + 1..LEAF_WORDS).rev() {
+            self.words[w] = self.words[w] << 1 | self.words[w - 1] >> 63;
+        }
+        let word = self.words[q];
+        self.words[q] = word & mask(r) | (word & !mask(r)) << 1 | (bit as u64) << r;
+        self.len += 1;
+        self.ones += bit as usize;
+    }
+
+    fn delete(&mut self, i: usize) -> bool {
+        let bit = self.get(i);
+        let (q, r) = (i / 64, i % 64);
+        let word = self.words[q];
+        self.words[q] = word & mask(r) | (word >> 1) & !mask(r);
+        for w in q + 1..LEAF_WORDS {
+            self.words[w - 1] |= self.words[w] << 63;
+            self.words[w] >>= 1;
+        }
+        self.len -= 1;
+        self.ones -= bit as usize;
+        bit
+    }
+
+    // Append the low `width` bits of `bits`.
+    fn push(&mut self, bits: u64, width: usize) {
+        debug_assert!(self.len + width <= LEAF_BITS);
+        if width == 0 {
+            return;
+        }
+        let bits = bits & mask(width);
+        let (q, r) = (self.len / 64, self.len % 64);
+        self.words[q] |= bits << r;
+        if r + width > 64 {
+            self.words[q + 1] |= bits >> (64 - r);
+        }
+        self.len += width;
+        self.ones += bits.ones();
+    }
+
+    fn append(&mut self, that: &Leaf) {
+        let mut i = 0;
+        while i < that.len {
+            let width = (that.len - i).min(64);
+            self.push(that.words[i / 64], width);
+            i += width;
+        }
+    }
+
+    // Split a full leaf into halves.
+    fn split(&self) -> (Leaf, Leaf) {
+        let half = LEAF_WORDS / 2;
+        let mut left = Leaf::default();
+        let mut right = Leaf::default();
+        for w in 0..half {
+            left.push(self.words[w], 64);
+            right.push(self.words[half + w], 64);
+        }
+        (left, right)
+    }
+
+    fn rank1(&self, i: usize) -> usize {
+        let (q, r) = (i / 64, i % 64);
+        let rank: usize = self.words[q].rank1(r);
+        self.words[..q].iter().fold(rank, |acc, w| acc + w.ones())
+    }
+
+    fn select(&self, c: usize, one: bool) -> usize {
+        let mut c = c;
+        for (i, &w) in self.words.iter().enumerate() {
+            let w = if one { w } else { !w };
+            let ones = w.ones();
+            if c < ones {
+                let p: usize = w.select1(c).unwrap();
+                return 64 * i + p;
+            }
+            c -= ones;
+        }
+        unreachable!("select out of leaf")
+    }
+}
+
+impl Default for Node {
+    fn default() -> Node {
+        Node::Leaf(Leaf::default())
+    }
+}
+
+impl Node {
+    fn len(&self) -> usize {
+        match self {
+            &Node::Leaf(ref leaf) => leaf.len,
+            &Node::Inner(ref inner) => inner.len,
+        }
+    }
+
+    fn ones(&self) -> usize {
+        match self {
+            &Node::Leaf(ref leaf) => leaf.ones,
+            &Node::Inner(ref inner) => inner.ones,
+        }
+    }
+
+    fn height(&self) -> usize {
+        match self {
+            &Node::Leaf(_) => 0,
+            &Node::Inner(ref inner) => inner.height,
+        }
+    }
+
+    fn inner(self) -> Inner {
+        match self {
+            Node::Inner(inner) => *inner,
+            Node::Leaf(_) => unreachable!("expected Node::Inner"),
+        }
+    }
+
+    fn join(left: Node, right: Node) -> Node {
+        let len = left.len() + right.len();
+        let ones = left.ones() + right.ones();
+        let height = 1 + cmp::max(left.height(), right.height());
+        Node::Inner(Box::new(Inner { left, right, len, ones, height }))
+    }
+
+    // Join subtrees whose heights differ by at most 2, rotating to restore balance.
+    fn balance(left: Node, right: Node) -> Node {
+        let (hl, hr) = (left.height(), right.height());
+        if hl > hr + 1 {
+            let Inner { left: ll, right: lr, .. } = left.inner();
+            if ll.height() >= lr.height() {
+                Node::join(ll, Node::join(lr, right))
+            } else {
+                let Inner { left: lrl, right: lrr, .. } = lr.inner();
+                Node::join(Node::join(ll, lrl), Node::join(lrr, right))
+            }
+        } else if hr > hl + 1 {
+            let Inner { left: rl, right: rr, .. } = right.inner();
+            if rr.height() >= rl.height() {
+                Node::join(Node::join(left, rl), rr)
+            } else {
+                let Inner { left: rll, right: rlr, .. } = rl.inner();
+                Node::join(Node::join(left, rll), Node::join(rlr, rr))
+            }
+        } else {
+            Node::join(left, right)
+        }
+    }
+
+    // Build a balanced tree of `leaves`.
+    fn build(leaves: &[Leaf]) -> Node {
+        match leaves.len() {
+            0 => Node::default(),
+            1 => Node::Leaf(leaves[0].clone()),
+            n => Node::join(Node::build(&leaves[..n / 2]), Node::build(&leaves[n / 2..])),
+        }
+    }
+
+    fn get(&self, i: usize) -> bool {
+        match self {
+            &Node::Leaf(ref leaf) => leaf.get(i),
+            &Node::Inner(ref inner) => {
+                let len = inner.left.len();
+                if i < len { inner.left.get(i) } else { inner.right.get(i - len) }
+            }
+        }
+    }
+
+    fn set(&mut self, i: usize, bit: bool) -> bool {
+        match self {
+            &mut Node::Leaf(ref mut leaf) => leaf.set(i, bit),
+            &mut Node::Inner(ref mut inner) => {
+                let len = inner.left.len();
+                let old = if i < len {
+                    inner.left.set(i, bit)
+                } else {
+                    inner.right.set(i - len, bit)
+                };
+                inner.ones = inner.ones + bit as usize - old as usize;
+                old
+            }
+        }
+    }
+
+    fn insert(self, i: usize, bit: bool) -> Node {
+        match self {
+            Node::Leaf(mut leaf) => {
+                if leaf.len < LEAF_BITS {
+                    leaf.insert(i, bit);
+                    return Node::Leaf(leaf);
+                }
+                let (mut left, mut right) = leaf.split();
+                if i <= left.len {
+                    left.insert(i, bit);
+                } else {
+                    right.insert(i - left.len, bit);
+                }
+                Node::join(Node::Leaf(left), Node::Leaf(right))
+            }
+            Node::Inner(inner) => {
+                let Inner { left, right, .. } = *inner;
+                let len = left.len();
+                if i <= len {
+                    Node::balance(left.insert(i, bit), right)
+                } else {
+                    Node::balance(left, right.insert(i - len, bit))
+                }
+            }
+        }
+    }
+
+    fn delete(self, i: usize) -> (Node, bool) {
+        match self {
+            Node::Leaf(mut leaf) => {
+                let bit = leaf.delete(i);
+                (Node::Leaf(leaf), bit)
+            }
+            Node::Inner(inner) => {
+                let Inner { left, right, .. } = *inner;
+                let len = left.len();
+                let (left, right, bit) = if i < len {
+                    let (left, bit) = left.delete(i);
+                    (left, right, bit)
+                } else {
+                    let (right, bit) = right.delete(i - len);
+                    (left, right, bit)
+                };
+                let node = match (left, right) {
+                    (left, right) if left.len() == 0 => right,
+                    (left, right) if right.len() == 0 => left,
+                    (Node::Leaf(mut left), Node::Leaf(right))
+                        if left.len + right.len <= MERGE_BITS =>
+                    {
+                        left.append(&right);
+                        Node::Leaf(left)
+                    }
+                    (left, right) => Node::balance(left, right),
+                };
+                (node, bit)
+            }
+        }
+    }
+
+    fn rank1(&self, i: usize) -> usize {
+        match self {
+            &Node::Leaf(ref leaf) => leaf.rank1(i),
+            &Node::Inner(ref inner) => {
+                let len = inner.left.len();
+                if i < len {
+                    inner.left.rank1(i)
+                } else {
+                    inner.left.ones() + inner.right.rank1(i - len)
+                }
+            }
+        }
+    }
+
+    // Position of the 'c+1'th one, or zero if not `one`.
+    fn select(&self, c: usize, one: bool) -> usize {
+        match self {
+            &Node::Leaf(ref leaf) => leaf.select(c, one),
+            &Node::Inner(ref inner) => {
+                let left = &inner.left;
+                let count = if one { left.ones() } else { left.len() - left.ones() };
+                if c < count {
+                    left.select(c, one)
+                } else {
+                    left.len() + inner.right.select(c - count, one)
+                }
+            }
+        }
+    }
+}
+
+impl DynamicBitVec {
+    pub fn new() -> DynamicBitVec {
+        DynamicBitVec::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Count non-zero bits.
+    pub fn ones(&self) -> usize {
+        self.root.ones()
+    }
+
+    /// Count zero bits.
+    pub fn zeros(&self) -> usize {
+        self.len() - self.ones()
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len(), "index out of bounds");
+        self.root.get(i)
+    }
+
+    /// Set the 'i+1'th bit and return its previous value.
+    pub fn set(&mut self, i: usize, bit: bool) -> bool {
+        assert!(i < self.len(), "index out of bounds");
+        self.root.set(i, bit)
+    }
+
+    /// Insert `bit` before the 'i+1'th bit, shifting the rest.
+    pub fn insert_bit(&mut self, i: usize, bit: bool) {
+        assert!(i <= self.len(), "index out of bounds");
+        self.root = mem::take(&mut self.root).insert(i, bit);
+    }
+
+    /// Remove the 'i+1'th bit, shifting the rest, and return it.
+    pub fn delete_bit(&mut self, i: usize) -> bool {
+        assert!(i < self.len(), "index out of bounds");
+        let (root, bit) = mem::take(&mut self.root).delete(i);
+        self.root = root;
+        bit
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        let len = self.len();
+        self.insert_bit(len, bit);
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { stack: vec![&self.root], leaf: None, pos: 0 }
+    }
+}
+
+impl Bits for DynamicBitVec {
+    const SIZE: usize = usize::MAX;
+
+    fn none() -> Self {
+        DynamicBitVec::new()
+    }
+
+    fn ones(&self) -> usize {
+        DynamicBitVec::ones(self)
+    }
+
+    fn zeros(&self) -> usize {
+        DynamicBitVec::zeros(self)
+    }
+}
+
+impl BitLen for DynamicBitVec {
+    fn len(&self) -> usize {
+        DynamicBitVec::len(self)
+    }
+
+    fn ones(&self) -> usize {
+        DynamicBitVec::ones(self)
+    }
+
+    fn zeros(&self) -> usize {
+        DynamicBitVec::zeros(self)
+    }
+}
+
+impl Rank for DynamicBitVec {
+    fn rank1(&self, i: usize) -> usize {
+        if i >= self.len() { self.ones() } else { self.root.rank1(i) }
+    }
+
+    fn rank0(&self, i: usize) -> usize {
+        i.min(self.len()) - self.rank1(i)
+    }
+}
+
+impl Select1 for DynamicBitVec {
+    fn select1(&self, c: usize) -> Option<usize> {
+        if c < self.ones() { Some(self.root.select(c, true)) } else { None }
+    }
+}
+
+impl Select0 for DynamicBitVec {
+    fn select0(&self, c: usize) -> Option<usize> {
+        if c < self.zeros() { Some(self.root.select(c, false)) } else { None }
+    }
+}
+
+impl FromIterator<bool> for DynamicBitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iterable: I) -> Self {
+        let mut leaves = Vec::new();
+        let mut leaf = Leaf::default();
+        for bit in iterable {
+            if leaf.len == LEAF_BITS {
+                leaves.push(mem::take(&mut leaf));
+            }
+            leaf.push(bit as u64, 1);
+        }
+        if leaf.len > 0 {
+            leaves.push(leaf);
+        }
+        DynamicBitVec { root: Node::build(&leaves) }
+    }
+}
+
+impl fmt::Debug for DynamicBitVec {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "DynamicBitVec{{len:{:?}, ones:{:?}}}", self.len(), self.ones())
+    }
+}
+
+pub struct Iter<'a> {
+    // Subtrees not visited yet, the next one last.
+    stack: Vec<&'a Node>,
+    leaf: Option<&'a Leaf>,
+    pos: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = bool;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(leaf) = self.leaf {
+                if self.pos < leaf.len {
+                    self.pos += 1;
+                    return Some(leaf.get(self.pos - 1));
+                }
+            }
+            match self.stack.pop()? {
+                &Node::Leaf(ref leaf) => {
+                    self.leaf = Some(leaf);
+                    self.pos = 0;
+                }
+                &Node::Inner(ref inner) => {
+                    self.stack.push(&inner.right);
+                    self.stack.push(&inner.left);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+extern crate rand;
+use self::rand::Rng;
+
+use super::*;
+
+// Ascending values mixing runs, dense and sparse stretches.
+fn gen<R: Rng>(size: usize, rng: &mut R) -> Vec<u64> {
+    let mut values = Vec::with_capacity(size);
+    let mut x = rng.gen_range(0, 1000);
+    while values.len() < size {
+        let (len, gap) = match rng.gen_range(0, 3) {
+            0 => (rng.gen_range(1, 500), 1),
+            1 => (rng.gen_range(1, 500), 3),
+            _ => (rng.gen_range(1, 50), 100_000),
+        };
+        for _ in 0..len {
+            values.push(x);
+            x += rng.gen_range(0, gap) + 1;
+        }
+    }
+    values
+}
+
+// Index and value of the first one not less than `x`.
+fn next_geq(values: &[u64], x: u64) -> Option<(usize, u64)> {
+    let i = values.partition_point(|&v| v < x);
+    values.get(i).map(|&v| (i, v))
+}
+
+#[test]
+fn elias_fano() {
+    let mut rng = rand::thread_rng();
+    for &size in &[0, 1, 2, 100, 1000, 10_000] {
+        let mut values = gen(size, &mut rng);
+        // Duplicates are kept.
+        for i in (0..values.len()).step_by(7).skip(1) {
+            values[i] = values[i - 1];
+        }
+        let ef = EliasFano::from_sorted_slice(&values);
+        assert_eq!(ef.len(), values.len());
+        assert_eq!(ef.iter().collect::<Vec<u64>>(), values);
+        for (i, &x) in values.iter().enumerate() {
+            assert_eq!(ef.get(i), Some(x));
+        }
+        assert_eq!(ef.get(values.len()), None);
+
+        let last = values.last().map_or(0, |&x| x);
+        for _ in 0..1000 {
+            let x = rng.gen_range(0, last + 10);
+            assert_eq!(ef.next_geq(x), next_geq(&values, x));
+        }
+    }
+}
+
+#[test]
+fn partitioned_elias_fano() {
+    let mut rng = rand::thread_rng();
+    for &size in &[0, 1, 127, 128, 129, 1000, 100_000] {
+        let values = gen(size, &mut rng);
+        let pef = PartitionedEliasFano::from_sorted_slice(&values);
+        assert_eq!(pef.len(), values.len());
+        assert_eq!(pef.iter().len(), values.len());
+        assert_eq!(pef.iter().collect::<Vec<u64>>(), values);
+        for (i, &x) in values.iter().enumerate() {
+            assert_eq!(pef.get(i), Some(x));
+            assert_eq!(pef.next_geq(x), Some((i, x)));
+        }
+        assert_eq!(pef.get(values.len()), None);
+
+        let last = values.last().map_or(0, |&x| x);
+        for _ in 0..1000 {
+            let x = rng.gen_range(0, last + 10);
+            assert_eq!(pef.next_geq(x), next_geq(&values, x));
+        }
+    }
+}
+
+#[test]
+fn partitioned_elias_fano_edges() {
+    // Duplicates are ignored.
+    let pef = PartitionedEliasFano::from_sorted_slice(&[1, 1, 2, 5, 5, 5]);
+    assert_eq!(pef.iter().collect::<Vec<u64>>(), vec![1, 2, 5]);
+
+    let values = [0, 1, u64::MAX - 1, u64::MAX];
+    let pef = PartitionedEliasFano::from_sorted_slice(&values);
+    assert_eq!(pef.iter().collect::<Vec<u64>>(), values);
+    assert_eq!(pef.next_geq(2), Some((2, u64::MAX - 1)));
+    assert_eq!(pef.next_geq(u64::MAX), Some((3, u64::MAX)));
+
+    let ef = EliasFano::from_sorted_slice(&values);
+    assert_eq!(ef.iter().collect::<Vec<u64>>(), values);
+    assert_eq!(ef.next_geq(2), Some((2, u64::MAX - 1)));
+
+    // A long run takes only chunk ends and offsets.
+    let values = (1000..1_000_000).collect::<Vec<u64>>();
+    let pef = PartitionedEliasFano::from_sorted_slice(&values);
+    assert!(pef.size_in_bytes() < values.len() / 64);
+    assert_eq!(pef.next_geq(0), Some((0, 1000)));
+    assert_eq!(pef.next_geq(999_999), Some((values.len() - 1, 999_999)));
+    assert_eq!(pef.next_geq(1_000_000), None);
+}
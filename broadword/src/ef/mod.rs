@@ -0,0 +1,253 @@
+//! Elias-Fano coded monotone sequences.
+//!
+//! Vigna, "Quasi-succinct indices", WSDM 2013;
+//! Ottaviano and Venturini, "Partitioned Elias-Fano indexes", SIGIR 2014.
+
+use alloc::vec::Vec;
+
+use super::bitbuf::BitBuf;
+
+mod partitioned;
+pub use self::partitioned::PartitionedEliasFano;
+
+#[cfg(test)]
+mod ef_test;
+
+// Every `SAMPLE`th one and zero of the high parts is sampled to speed up select.
+const SAMPLE: usize = 256;
+
+/// Non-decreasing sequence of `u64`.
+///
+/// Each value is split into `width` low bits, stored as is,
+/// and the high bits, stored in unary as gaps between ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EliasFano {
+    len: usize,
+    width: usize,
+    high_len: usize,
+    // Low parts, then high parts.
+    buf: BitBuf,
+    // Positions in high parts of every `SAMPLE`th one and zero.
+    samples1: Vec<usize>,
+    samples0: Vec<usize>,
+}
+
+// Width of low parts of `n` values whose largest one is `last`.
+fn low_width(n: usize, last: u64) -> usize {
+    let q = (last as u128 + 1) / n as u128;
+    if q <= 1 { 0 } else { (127 - q.leading_zeros() as usize).min(63) }
+}
+
+// Count of bits of the high parts.
+fn high_len(n: usize, last: u64, width: usize) -> usize {
+    n + (last >> width) as usize + 1
+}
+
+// Append `values - base`, non-decreasing, to `buf` and return the width of low parts.
+fn encode(values: &[u64], base: u64, buf: &mut BitBuf) -> usize {
+    let n = values.len();
+    let last = values[n - 1] - base;
+    let width = low_width(n, last);
+    for &v in values {
+        buf.push(v - base, width);
+    }
+    let high = buf.len();
+    buf.push_zeros(high_len(n, last, width));
+    for (k, &v) in values.iter().enumerate() {
+        buf.set(high + ((v - base) >> width) as usize + k);
+    }
+    width
+}
+
+// A sequence encoded by `encode` at `low` in `buf`.
+#[derive(Clone, Copy)]
+struct View<'a> {
+    buf: &'a BitBuf,
+    len: usize,
+    width: usize,
+    low: usize,
+    high: usize,
+    high_len: usize,
+    samples1: &'a [usize],
+    samples0: &'a [usize],
+}
+
+impl<'a> View<'a> {
+    fn new(buf: &'a BitBuf, low: usize, len: usize, last: u64, width: usize) -> View<'a> {
+        let high = low + len * width;
+        let high_len = high_len(len, last, width);
+        View { buf, len, width, low, high, high_len, samples1: &[], samples0: &[] }
+    }
+
+    fn low(&self, k: usize) -> u64 {
+        self.buf.get_bits(self.low + k * self.width, self.width)
+    }
+
+    fn value(&self, k: usize, pos: usize) -> u64 {
+        ((pos - k) as u64) << self.width | self.low(k)
+    }
+
+    // Position in high parts of the 'c+1'th one, or zero if not `one`.
+    fn select(&self, c: usize, one: bool) -> usize {
+        let samples = if one { self.samples1 } else { self.samples0 };
+        let (from, c) = match samples.get(c / SAMPLE) {
+            Some(&pos) => (pos, c % SAMPLE),
+            None => (0, c),
+        };
+        let end = self.high + self.high_len;
+        self.buf.select(self.high + from, end, c, one).unwrap() - self.high
+    }
+
+    fn get(&self, k: usize) -> u64 {
+        self.value(k, self.select(k, true))
+    }
+
+    // Index and value of the first one not less than `x`.
+    fn next_geq(&self, x: u64) -> Option<(usize, u64)> {
+        let hx = x >> self.width;
+        let (mut pos, mut k) = if hx == 0 {
+            (0, 0)
+        } else {
+            // The `hx`th zero closes the buckets below `hx`.
+            if hx > (self.high_len - self.len) as u64 {
+                return None;
+            }
+            let hx = hx as usize;
+            let pos = self.select(hx - 1, false) + 1;
+            (pos, pos - hx)
+        };
+        while k < self.len {
+            if self.buf.get(self.high + pos) {
+                let v = self.value(k, pos);
+                if v >= x {
+                    return Some((k, v));
+                }
+                k += 1;
+            }
+            pos += 1;
+        }
+        None
+    }
+
+    // Append all values plus `base` to `out`.
+    fn decode(&self, base: u64, out: &mut Vec<u64>) {
+        let (mut pos, mut k) = (0, 0);
+        while k < self.len {
+            if self.buf.get(self.high + pos) {
+                out.push(base + self.value(k, pos));
+                k += 1;
+            }
+            pos += 1;
+        }
+    }
+}
+
+impl EliasFano {
+    /// Build from non-decreasing values, duplicates are kept.
+    pub fn from_sorted_slice(values: &[u64]) -> EliasFano {
+        debug_assert!(values.windows(2).all(|w| w[0] <= w[1]));
+        if values.is_empty() {
+            return EliasFano::default();
+        }
+        let len = values.len();
+        let mut buf = BitBuf::default();
+        let width = encode(values, 0, &mut buf);
+        let high_len = high_len(len, values[len - 1], width);
+
+        let high = len * width;
+        let (mut samples1, mut samples0) = (Vec::new(), Vec::new());
+        let (mut ones, mut zeros) = (0, 0);
+        for pos in 0..high_len {
+            if buf.get(high + pos) {
+                if ones % SAMPLE == 0 {
+                    samples1.push(pos);
+                }
+                ones += 1;
+            } else {
+                if zeros % SAMPLE == 0 {
+                    samples0.push(pos);
+                }
+                zeros += 1;
+            }
+        }
+        EliasFano { len, width, high_len, buf, samples1, samples0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        let samples = (self.samples1.len() + self.samples0.len()) * core::mem::size_of::<usize>();
+        self.buf.size_in_bytes() + samples
+    }
+
+    fn view(&self) -> View<'_> {
+        View {
+            buf: &self.buf,
+            len: self.len,
+            width: self.width,
+            low: 0,
+            high: self.len * self.width,
+            high_len: self.high_len,
+            samples1: &self.samples1,
+            samples0: &self.samples0,
+        }
+    }
+
+    /// Return the 'i+1'th value.
+    pub fn get(&self, i: usize) -> Option<u64> {
+        if i < self.len { Some(self.view().get(i)) } else { None }
+    }
+
+    /// Index and value of the first value not less than `x`.
+    pub fn next_geq(&self, x: u64) -> Option<(usize, u64)> {
+        if self.len == 0 {
+            return None;
+        }
+        self.view().next_geq(x)
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { view: self.view(), pos: 0, k: 0 }
+    }
+}
+
+pub struct Iter<'a> {
+    view: View<'a>,
+    pos: usize,
+    k: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u64;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.k == self.view.len {
+            return None;
+        }
+        while !self.view.buf.get(self.view.high + self.pos) {
+            self.pos += 1;
+        }
+        let v = self.view.value(self.k, self.pos);
+        self.k += 1;
+        self.pos += 1;
+        Some(v)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rest = self.view.len - self.k;
+        (rest, Some(rest))
+    }
+}
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+impl<'a> IntoIterator for &'a EliasFano {
+    type Item = u64;
+    type IntoIter = Iter<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
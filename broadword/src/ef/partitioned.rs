@@ -0,0 +1,230 @@
+use alloc::vec::Vec;
+
+use super::{encode, low_width, BitBuf, EliasFano, View};
+
+// Count of values per chunk, but the last.
+const CHUNK: usize = 128;
+
+// Encoding of a chunk of `n` values, relative to the chunk base, whose largest one is `span`.
+// It is picked by size only, so it is not stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    // Values are `0..n`, nothing is stored.
+    Run,
+    // Bitmap of `span + 1` bits.
+    Dense,
+    // Elias-Fano with low parts of that width.
+    Sparse(usize),
+}
+
+impl Kind {
+    fn new(n: usize, span: u64) -> Kind {
+        let universe = span as u128 + 1;
+        if universe == n as u128 {
+            return Kind::Run;
+        }
+        let width = low_width(n, span);
+        let sparse = (n * (width + 1)) as u128 + (span >> width) as u128 + 1;
+        if universe <= sparse { Kind::Dense } else { Kind::Sparse(width) }
+    }
+}
+
+/// Strictly ascending sequence of `u64`, split into chunks of 128 values.
+///
+/// Each chunk is encoded relative to the end of the previous one, as a run,
+/// a bitmap or an Elias-Fano sequence, whichever is smallest.
+/// Chunk ends and offsets are themselves Elias-Fano coded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PartitionedEliasFano {
+    len: usize,
+    // Last value of each chunk.
+    uppers: EliasFano,
+    // Bit offset of each chunk in `data`.
+    offsets: EliasFano,
+    data: BitBuf,
+}
+
+// A chunk of values in `base..=base + span`.
+struct Chunk<'a> {
+    data: &'a BitBuf,
+    base: u64,
+    span: u64,
+    len: usize,
+    offset: usize,
+    kind: Kind,
+}
+
+impl<'a> Chunk<'a> {
+    fn view(&self, width: usize) -> View<'a> {
+        View::new(self.data, self.offset, self.len, self.span, width)
+    }
+
+    fn end(&self) -> usize {
+        self.offset + self.span as usize + 1
+    }
+
+    fn get(&self, k: usize) -> u64 {
+        let r = match self.kind {
+            Kind::Run => k as u64,
+            Kind::Dense => {
+                let pos = self.data.select(self.offset, self.end(), k, true).unwrap();
+                (pos - self.offset) as u64
+            }
+            Kind::Sparse(width) => self.view(width).get(k),
+        };
+        self.base + r
+    }
+
+    // Index and value of the first value not less than `x`,
+    // which is at least `base` and at most the last value.
+    fn next_geq(&self, x: u64) -> (usize, u64) {
+        let r = x - self.base;
+        match self.kind {
+            Kind::Run => (r as usize, x),
+            Kind::Dense => {
+                let from = self.offset + r as usize;
+                let pos = self.data.select(from, self.end(), 0, true).unwrap();
+                let k = self.data.rank1(self.offset, pos);
+                (k, self.base + (pos - self.offset) as u64)
+            }
+            Kind::Sparse(width) => {
+                let (k, r) = self.view(width).next_geq(r).unwrap();
+                (k, self.base + r)
+            }
+        }
+    }
+
+    fn decode(&self, out: &mut Vec<u64>) {
+        match self.kind {
+            Kind::Run => out.extend((0..self.len as u64).map(|r| self.base + r)),
+            Kind::Dense => {
+                let mut pos = self.offset;
+                while let Some(p) = self.data.select(pos, self.end(), 0, true) {
+                    out.push(self.base + (p - self.offset) as u64);
+                    pos = p + 1;
+                }
+            }
+            Kind::Sparse(width) => self.view(width).decode(self.base, out),
+        }
+    }
+}
+
+impl PartitionedEliasFano {
+    /// Build from ascending values, duplicates are ignored.
+    pub fn from_sorted_slice(values: &[u64]) -> PartitionedEliasFano {
+        debug_assert!(values.windows(2).all(|w| w[0] <= w[1]));
+        let mut data = BitBuf::default();
+        let (mut uppers, mut offsets) = (Vec::new(), Vec::new());
+        let mut len = 0;
+        let mut chunk = Vec::with_capacity(CHUNK);
+        let distinct = values.iter().enumerate().filter(|&(i, &x)| i == 0 || values[i - 1] != x);
+        let mut iter = distinct.map(|(_, &x)| x).peekable();
+        while let Some(x) = iter.next() {
+            chunk.push(x);
+            if chunk.len() < CHUNK && iter.peek().is_some() {
+                continue;
+            }
+            let base = uppers.last().map_or(0, |&upper| upper + 1);
+            let span = x - base;
+            offsets.push(data.len() as u64);
+            match Kind::new(chunk.len(), span) {
+                Kind::Run => {}
+                Kind::Dense => {
+                    let offset = data.len();
+                    data.push_zeros(span as usize + 1);
+                    for &v in &chunk {
+                        data.set(offset + (v - base) as usize);
+                    }
+                }
+                Kind::Sparse(width) => {
+                    let w = encode(&chunk, base, &mut data);
+                    debug_assert_eq!(w, width);
+                }
+            }
+            uppers.push(x);
+            len += chunk.len();
+            chunk.clear();
+        }
+        PartitionedEliasFano {
+            len,
+            uppers: EliasFano::from_sorted_slice(&uppers),
+            offsets: EliasFano::from_sorted_slice(&offsets),
+            data,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.uppers.size_in_bytes() + self.offsets.size_in_bytes() + self.data.size_in_bytes()
+    }
+
+    fn chunk(&self, i: usize) -> Chunk<'_> {
+        let base = if i == 0 { 0 } else { self.uppers.get(i - 1).unwrap() + 1 };
+        let span = self.uppers.get(i).unwrap() - base;
+        let len = (self.len - i * CHUNK).min(CHUNK);
+        let offset = self.offsets.get(i).unwrap() as usize;
+        Chunk { data: &self.data, base, span, len, offset, kind: Kind::new(len, span) }
+    }
+
+    /// Return the 'i+1'th value.
+    pub fn get(&self, i: usize) -> Option<u64> {
+        if i < self.len { Some(self.chunk(i / CHUNK).get(i % CHUNK)) } else { None }
+    }
+
+    /// Index and value of the first value not less than `x`.
+    pub fn next_geq(&self, x: u64) -> Option<(usize, u64)> {
+        let (i, _) = self.uppers.next_geq(x)?;
+        let (k, v) = self.chunk(i).next_geq(x);
+        Some((i * CHUNK + k, v))
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { pef: self, chunk: 0, values: Vec::with_capacity(CHUNK), pos: 0 }
+    }
+}
+
+/// Values of a `PartitionedEliasFano`, decoded a chunk at a time.
+pub struct Iter<'a> {
+    pef: &'a PartitionedEliasFano,
+    chunk: usize,
+    values: Vec<u64>,
+    pos: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u64;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos == self.values.len() {
+            if self.chunk * CHUNK >= self.pef.len {
+                return None;
+            }
+            self.values.clear();
+            self.pef.chunk(self.chunk).decode(&mut self.values);
+            self.chunk += 1;
+            self.pos = 0;
+        }
+        self.pos += 1;
+        Some(self.values[self.pos - 1])
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let decoded = (self.chunk * CHUNK).min(self.pef.len);
+        let rest = self.pef.len - decoded + self.values.len() - self.pos;
+        (rest, Some(rest))
+    }
+}
+impl<'a> ExactSizeIterator for Iter<'a> {}
+
+impl<'a> IntoIterator for &'a PartitionedEliasFano {
+    type Item = u64;
+    type IntoIter = Iter<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
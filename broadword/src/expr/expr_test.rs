@@ -0,0 +1,94 @@
+extern crate rand;
+use self::rand::Rng;
+
+use std::collections::BTreeSet;
+
+use super::*;
+
+struct Terms {
+    bitmaps: Vec<Bitmap>,
+    universe: Bitmap,
+}
+
+impl Provider<usize> for Terms {
+    fn bitmap(&self, key: &usize) -> Option<&Bitmap> {
+        self.bitmaps.get(*key)
+    }
+    fn universe(&self) -> &Bitmap {
+        &self.universe
+    }
+}
+
+// Terms of various densities, key 5 is missing and some elements are out of the universe.
+fn gen_terms<R: Rng>(rng: &mut R) -> (Terms, Vec<BTreeSet<u32>>, BTreeSet<u32>) {
+    let mut sets = Vec::new();
+    for &size in &[0, 10, 1000, 5000, 20000] {
+        let set = (0..size).map(|_| rng.gen_range(0, 1 << 16)).collect::<BTreeSet<u32>>();
+        sets.push(set);
+    }
+    let universe = (0..3 << 14).collect::<BTreeSet<u32>>();
+    let terms = Terms {
+        bitmaps: sets.iter().map(|set| set.iter().collect()).collect(),
+        universe: universe.iter().collect(),
+    };
+    (terms, sets, universe)
+}
+
+fn gen_expr<R: Rng>(depth: usize, rng: &mut R) -> Expr<usize> {
+    let leaf = Expr::Leaf(rng.gen_range(0, 6));
+    if depth == 0 {
+        return leaf;
+    }
+    let len = rng.gen_range(0, 4);
+    let exprs = (0..len).map(|_| gen_expr(depth - 1, rng)).collect();
+    match rng.gen_range(0, 6) {
+        0 => leaf,
+        1 => Expr::And(exprs),
+        2 => Expr::Or(exprs),
+        3 => Expr::Xor(exprs),
+        4 => gen_expr(depth - 1, rng) - gen_expr(depth - 1, rng),
+        _ => !gen_expr(depth - 1, rng),
+    }
+}
+
+fn oracle(expr: &Expr<usize>, sets: &[BTreeSet<u32>], universe: &BTreeSet<u32>) -> BTreeSet<u32> {
+    let eval = |expr| oracle(expr, sets, universe);
+    match expr {
+        &Expr::Leaf(key) => sets.get(key).cloned().unwrap_or_default(),
+        &Expr::And(ref exprs) => {
+            let mut sets = exprs.iter().map(eval);
+            let first = sets.next().unwrap_or_else(|| universe.clone());
+            sets.fold(first, |acc, set| &acc & &set)
+        }
+        &Expr::Or(ref exprs) => exprs.iter().fold(BTreeSet::new(), |acc, expr| &acc | &eval(expr)),
+        &Expr::Xor(ref exprs) => exprs.iter().fold(BTreeSet::new(), |acc, expr| &acc ^ &eval(expr)),
+        &Expr::AndNot(ref lhs, ref rhs) => &eval(lhs) - &eval(rhs),
+        &Expr::Not(ref expr) => universe - &eval(expr),
+    }
+}
+
+#[test]
+fn expr_ops() {
+    let (a, b, c, d) = (Expr::Leaf(0), Expr::Leaf(1), Expr::Leaf(2), Expr::Leaf(3));
+    let expr = (a.clone() | b.clone()) & !c.clone() ^ d.clone();
+    let want = Expr::Xor(vec![Expr::And(vec![Expr::Or(vec![a.clone(), b.clone()]),
+                                              Expr::Not(Box::new(c.clone()))]),
+                              d.clone()]);
+    assert_eq!(expr, want);
+    assert_eq!(a.clone() & b.clone() & c.clone(), Expr::And(vec![a.clone(), b.clone(), c.clone()]));
+    assert_eq!(a.clone() - b.clone(), Expr::AndNot(Box::new(a), Box::new(b)));
+}
+
+#[test]
+fn expr_eval_iter() {
+    let mut rng = rand::thread_rng();
+    let (terms, sets, universe) = gen_terms(&mut rng);
+    for _ in 0..200 {
+        let expr = gen_expr(3, &mut rng);
+        let want = oracle(&expr, &sets, &universe);
+        let eval = expr.eval(&terms);
+        assert!(eval.iter().eq(want.iter().cloned()), "{:?}", expr);
+        assert_eq!(eval.ones(), want.len() as u64);
+        assert!(expr.iter(&terms).eq(want.iter().cloned()), "{:?}", expr);
+    }
+}
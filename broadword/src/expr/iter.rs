@@ -0,0 +1,153 @@
+use core::iter::{self, Peekable};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::Bitmap;
+
+pub type Stream<'a> = Box<dyn Iterator<Item = u32> + 'a>;
+
+/// Ascending elements of an `Expr`.
+pub struct Iter<'a> {
+    stream: Stream<'a>,
+}
+
+impl<'a> Iter<'a> {
+    pub fn new(stream: Stream<'a>) -> Iter<'a> {
+        Iter { stream }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u32;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stream.next()
+    }
+}
+
+// Each stream below yields ascending elements without duplicates,
+// given that its operands do so.
+
+pub fn bitmap<'a>(bitmap: Option<&'a Bitmap>) -> Stream<'a> {
+    match bitmap {
+        Some(bitmap) => Box::new(bitmap.iter()),
+        None => Box::new(iter::empty()),
+    }
+}
+
+pub fn and<'a>(streams: Vec<Stream<'a>>) -> Stream<'a> {
+    Box::new(And(peekable(streams)))
+}
+
+pub fn or<'a>(streams: Vec<Stream<'a>>) -> Stream<'a> {
+    Box::new(Or(peekable(streams)))
+}
+
+pub fn xor<'a>(streams: Vec<Stream<'a>>) -> Stream<'a> {
+    Box::new(Xor(peekable(streams)))
+}
+
+pub fn and_not<'a>(lhs: Stream<'a>, rhs: Stream<'a>) -> Stream<'a> {
+    Box::new(AndNot(lhs, rhs.peekable()))
+}
+
+fn peekable<'a>(streams: Vec<Stream<'a>>) -> Vec<Peekable<Stream<'a>>> {
+    streams.into_iter().map(|stream| stream.peekable()).collect()
+}
+
+// Smallest head of `streams`, and how many streams have it.
+fn min<'a>(streams: &mut [Peekable<Stream<'a>>]) -> Option<(u32, usize)> {
+    let mut min = None;
+    for stream in streams.iter_mut() {
+        if let Some(&x) = stream.peek() {
+            min = match min {
+                Some((m, n)) if m == x => Some((m, n + 1)),
+                Some((m, n)) if m < x => Some((m, n)),
+                _ => Some((x, 1)),
+            };
+        }
+    }
+    if let Some((m, _)) = min {
+        for stream in streams.iter_mut() {
+            if stream.peek() == Some(&m) {
+                stream.next();
+            }
+        }
+    }
+    min
+}
+
+struct And<'a>(Vec<Peekable<Stream<'a>>>);
+
+impl<'a> Iterator for And<'a> {
+    type Item = u32;
+    fn next(&mut self) -> Option<Self::Item> {
+        // Leapfrog: advance every stream up to the largest head seen so far.
+        let mut target = *self.0.first_mut()?.peek()?;
+        loop {
+            let mut agreed = true;
+            for stream in self.0.iter_mut() {
+                loop {
+                    match stream.peek() {
+                        None => return None,
+                        Some(&x) if x < target => {
+                            stream.next();
+                        }
+                        Some(&x) => {
+                            if x > target {
+                                target = x;
+                                agreed = false;
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+            if agreed {
+                for stream in self.0.iter_mut() {
+                    stream.next();
+                }
+                return Some(target);
+            }
+        }
+    }
+}
+
+struct Or<'a>(Vec<Peekable<Stream<'a>>>);
+
+impl<'a> Iterator for Or<'a> {
+    type Item = u32;
+    fn next(&mut self) -> Option<Self::Item> {
+        min(&mut self.0).map(|(x, _)| x)
+    }
+}
+
+struct Xor<'a>(Vec<Peekable<Stream<'a>>>);
+
+impl<'a> Iterator for Xor<'a> {
+    type Item = u32;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (x, n) = min(&mut self.0)?;
+            if n % 2 == 1 {
+                return Some(x);
+            }
+        }
+    }
+}
+
+struct AndNot<'a>(Stream<'a>, Peekable<Stream<'a>>);
+
+impl<'a> Iterator for AndNot<'a> {
+    type Item = u32;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let x = self.0.next()?;
+            while self.1.peek().is_some_and(|&y| y < x) {
+                self.1.next();
+            }
+            if self.1.peek() != Some(&x) {
+                return Some(x);
+            }
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Boolean expressions over named bitmaps.
+
+use core::ops;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::Bitmap;
+
+mod iter;
+pub use self::iter::Iter;
+
+#[cfg(test)]
+mod expr_test;
+
+/// Resolve leaves of an `Expr` to bitmaps.
+pub trait Provider<K> {
+    /// Bitmap of `key`, `None` is the same as an empty bitmap.
+    fn bitmap(&self, key: &K) -> Option<&Bitmap>;
+
+    /// All elements, the domain of `Not`.
+    fn universe(&self) -> &Bitmap;
+}
+
+/// Expression tree, operators build it from leaves: `(a | b) & !c ^ d`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr<K> {
+    Leaf(K),
+    /// Intersection, the universe if empty.
+    And(Vec<Expr<K>>),
+    /// Union, empty if empty.
+    Or(Vec<Expr<K>>),
+    /// Elements in an odd count of operands, empty if empty.
+    Xor(Vec<Expr<K>>),
+    /// Elements of the left operand but not of the right one.
+    AndNot(Box<Expr<K>>, Box<Expr<K>>),
+    /// Complement within the universe.
+    Not(Box<Expr<K>>),
+}
+
+impl<K> Expr<K> {
+    /// Evaluate into a bitmap.
+    ///
+    /// Operands of `And` are evaluated from the smallest estimated one,
+    /// and each operand is evaluated only within the result so far.
+    pub fn eval<P: Provider<K>>(&self, provider: &P) -> Bitmap {
+        self.eval_in(provider, None)
+    }
+
+    /// Iterate the elements in ascending order, without materializing intermediates.
+    pub fn iter<'a, P: Provider<K>>(&'a self, provider: &'a P) -> Iter<'a> {
+        Iter::new(self.stream(provider))
+    }
+
+    // Evaluate the intersection of this and `filter`, if any.
+    fn eval_in<P: Provider<K>>(&self, p: &P, filter: Option<&Bitmap>) -> Bitmap {
+        match self {
+            &Expr::Leaf(ref key) => {
+                match (p.bitmap(key), filter) {
+                    (Some(bitmap), Some(filter)) => bitmap & filter,
+                    (Some(bitmap), None) => bitmap.clone(),
+                    (None, _) => Bitmap::new(),
+                }
+            }
+            &Expr::And(ref exprs) => {
+                let mut acc: Option<Bitmap> = None;
+                for expr in sorted(exprs, p) {
+                    let bitmap = expr.eval_in(p, acc.as_ref().or(filter));
+                    let empty = bitmap.is_empty();
+                    acc = Some(bitmap);
+                    if empty {
+                        break;
+                    }
+                }
+                acc.unwrap_or_else(|| universe_in(p, filter))
+            }
+            &Expr::Or(ref exprs) => {
+                let mut acc = Bitmap::new();
+                for expr in exprs {
+                    acc |= &expr.eval_in(p, filter);
+                }
+                acc
+            }
+            &Expr::Xor(ref exprs) => {
+                let mut acc = Bitmap::new();
+                for expr in exprs {
+                    acc ^= &expr.eval_in(p, filter);
+                }
+                acc
+            }
+            &Expr::AndNot(ref lhs, ref rhs) => {
+                let mut acc = lhs.eval_in(p, filter);
+                if !acc.is_empty() {
+                    let rhs = rhs.eval_in(p, Some(&acc));
+                    acc -= &rhs;
+                }
+                acc
+            }
+            &Expr::Not(ref expr) => {
+                let mut acc = universe_in(p, filter);
+                let expr = expr.eval_in(p, Some(&acc));
+                acc -= &expr;
+                acc
+            }
+        }
+    }
+
+    // Upper bound of the count of elements, used to order operands.
+    fn estimate<P: Provider<K>>(&self, p: &P) -> u64 {
+        match self {
+            &Expr::Leaf(ref key) => p.bitmap(key).map_or(0, |bitmap| bitmap.ones()),
+            &Expr::And(ref exprs) => {
+                let min = exprs.iter().map(|expr| expr.estimate(p)).min();
+                min.unwrap_or_else(|| p.universe().ones())
+            }
+            &Expr::Or(ref exprs) | &Expr::Xor(ref exprs) => {
+                exprs.iter().fold(0, |acc, expr| acc.saturating_add(expr.estimate(p)))
+            }
+            &Expr::AndNot(ref lhs, _) => lhs.estimate(p),
+            &Expr::Not(_) => p.universe().ones(),
+        }
+    }
+
+    fn stream<'a, P: Provider<K>>(&'a self, p: &'a P) -> iter::Stream<'a> {
+        match self {
+            &Expr::Leaf(ref key) => iter::bitmap(p.bitmap(key)),
+            &Expr::And(ref exprs) => {
+                // Intersect positive operands, then remove the union of negated ones.
+                // Negated operands are within the universe, so is the result.
+                let (nots, exprs): (Vec<&Expr<K>>, Vec<&Expr<K>>) =
+                    sorted(exprs, p).into_iter().partition(|expr| expr.is_not());
+                let mut streams = exprs.iter().map(|expr| expr.stream(p)).collect::<Vec<_>>();
+                if !nots.is_empty() || streams.is_empty() {
+                    streams.push(iter::bitmap(Some(p.universe())));
+                }
+                let lhs = if streams.len() == 1 {
+                    streams.pop().unwrap()
+                } else {
+                    iter::and(streams)
+                };
+                if nots.is_empty() {
+                    return lhs;
+                }
+                let rhs = nots.iter().filter_map(|expr| match *expr {
+                    &Expr::Not(ref expr) => Some(expr.stream(p)),
+                    _ => None,
+                });
+                iter::and_not(lhs, iter::or(rhs.collect()))
+            }
+            &Expr::Or(ref exprs) => iter::or(exprs.iter().map(|expr| expr.stream(p)).collect()),
+            &Expr::Xor(ref exprs) => iter::xor(exprs.iter().map(|expr| expr.stream(p)).collect()),
+            &Expr::AndNot(ref lhs, ref rhs) => iter::and_not(lhs.stream(p), rhs.stream(p)),
+            &Expr::Not(ref expr) => {
+                iter::and_not(iter::bitmap(Some(p.universe())), expr.stream(p))
+            }
+        }
+    }
+
+    fn is_not(&self) -> bool {
+        matches!(self, &Expr::Not(_))
+    }
+}
+
+// Operands ordered by estimated count of elements, negated ones last.
+fn sorted<'a, K, P: Provider<K>>(exprs: &'a [Expr<K>], p: &P) -> Vec<&'a Expr<K>> {
+    let mut keyed = exprs.iter().map(|expr| ((expr.is_not(), expr.estimate(p)), expr)).collect::<Vec<_>>();
+    keyed.sort_by_key(|&(key, _)| key);
+    keyed.into_iter().map(|(_, expr)| expr).collect()
+}
+
+fn universe_in<K, P: Provider<K>>(p: &P, filter: Option<&Bitmap>) -> Bitmap {
+    match filter {
+        Some(filter) => p.universe() & filter,
+        None => p.universe().clone(),
+    }
+}
+
+impl<K> ops::BitAnd for Expr<K> {
+    type Output = Expr<K>;
+    fn bitand(self, that: Expr<K>) -> Expr<K> {
+        match self {
+            Expr::And(mut exprs) => {
+                exprs.push(that);
+                Expr::And(exprs)
+            }
+            this => Expr::And(vec![this, that]),
+        }
+    }
+}
+
+impl<K> ops::BitOr for Expr<K> {
+    type Output = Expr<K>;
+    fn bitor(self, that: Expr<K>) -> Expr<K> {
+        match self {
+            Expr::Or(mut exprs) => {
+                exprs.push(that);
+                Expr::Or(exprs)
+            }
+            this => Expr::Or(vec![this, that]),
+        }
+    }
+}
+
+impl<K> ops::BitXor for Expr<K> {
+    type Output = Expr<K>;
+    fn bitxor(self, that: Expr<K>) -> Expr<K> {
+        match self {
+            Expr::Xor(mut exprs) => {
+                exprs.push(that);
+                Expr::Xor(exprs)
+            }
+            this => Expr::Xor(vec![this, that]),
+        }
+    }
+}
+
+impl<K> ops::Sub for Expr<K> {
+    type Output = Expr<K>;
+    fn sub(self, that: Expr<K>) -> Expr<K> {
+        Expr::AndNot(Box::new(self), Box::new(that))
+    }
+}
+
+impl<K> ops::Not for Expr<K> {
+    type Output = Expr<K>;
+    fn not(self) -> Expr<K> {
+        Expr::Not(Box::new(self))
+    }
+}
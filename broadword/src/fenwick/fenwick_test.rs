@@ -0,0 +1,59 @@
+extern crate rand;
+use self::rand::Rng;
+
+use super::*;
+
+fn check(fenwick: &FenwickBitVec, bits: &[bool]) {
+    let (mut rank1, mut rank0) = (0, 0);
+    for (i, &b) in bits.iter().enumerate() {
+        assert_eq!(fenwick.get(i), b);
+        assert_eq!(fenwick.rank1(i), rank1);
+        assert_eq!(fenwick.rank0(i), rank0);
+        if b {
+            assert_eq!(fenwick.select1(rank1), Some(i));
+            rank1 += 1;
+        } else {
+            assert_eq!(fenwick.select0(rank0), Some(i));
+            rank0 += 1;
+        }
+    }
+    assert_eq!(fenwick.ones(), rank1);
+    assert_eq!(fenwick.zeros(), rank0);
+    assert_eq!(fenwick.rank1(bits.len()), rank1);
+    assert_eq!(fenwick.select1(rank1), None);
+    assert_eq!(fenwick.select0(rank0), None);
+}
+
+#[test]
+fn fenwick_set_clear() {
+    let mut rng = rand::thread_rng();
+    for &len in &[0, 1, 64, 511, 512, 513, 5000, 40_000] {
+        let words = (0..len / 64 + 1).map(|_| rng.gen()).collect::<Vec<u64>>();
+        let mut fenwick = FenwickBitVec::from_words(&words, len);
+        let mut bits = (0..len).map(|i| words[i / 64] & 1 << (i % 64) != 0).collect::<Vec<bool>>();
+        check(&fenwick, &bits);
+        if len == 0 {
+            continue;
+        }
+        for _ in 0..2000 {
+            let i = rng.gen_range(0, len);
+            if rng.gen() {
+                assert_eq!(fenwick.set(i), !bits[i]);
+                bits[i] = true;
+            } else {
+                assert_eq!(fenwick.clear(i), bits[i]);
+                bits[i] = false;
+            }
+        }
+        check(&fenwick, &bits);
+
+        // Clear all, then take free slots in order.
+        let mut fenwick = FenwickBitVec::new(len);
+        for i in 0..len {
+            assert_eq!(fenwick.select0(0), Some(i));
+            assert!(fenwick.set(i));
+        }
+        assert_eq!(fenwick.select0(0), None);
+        assert_eq!(fenwick.ones(), len);
+    }
+}
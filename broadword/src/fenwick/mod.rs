@@ -0,0 +1,228 @@
+//! Mutable bit vector of a fixed length with rank and select.
+//!
+//! Fenwick, "A new data structure for cumulative frequency tables", 1994;
+//! Marchini and Vigna, "Compact Fenwick trees for dynamic ranking and selection", 2019.
+
+use alloc::vec::Vec;
+
+use super::{BitLen, Bits, Rank, Select0, Select1};
+
+#[cfg(test)]
+mod fenwick_test;
+
+// Ones are counted per block of a cache line.
+const BLOCK_WORDS: usize = 8;
+const BLOCK_BITS: usize = BLOCK_WORDS * 64;
+
+/// Bit vector whose bits can be set and cleared, but not inserted or removed.
+///
+/// Ones are counted per block of 512 bits in a Fenwick tree,
+/// so that updates, rank and select take O(log n).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FenwickBitVec {
+    len: usize,
+    words: Vec<u64>,
+    // 1-based, `tree[i]` counts ones of blocks in `i - (i & -i)..i`.
+    tree: Vec<usize>,
+}
+
+impl Default for FenwickBitVec {
+    fn default() -> FenwickBitVec {
+        FenwickBitVec::new(0)
+    }
+}
+
+impl FenwickBitVec {
+    /// Bit vector of `len` zeros.
+    pub fn new(len: usize) -> FenwickBitVec {
+        let blocks = len.div_ceil(BLOCK_BITS);
+        let words = vec![0; blocks * BLOCK_WORDS];
+        FenwickBitVec { len, words, tree: vec![0; blocks + 1] }
+    }
+
+    /// Build from the first `len` bits of `words`.
+    pub fn from_words(words: &[u64], len: usize) -> FenwickBitVec {
+        assert!(len <= words.len() * 64);
+        let mut fenwick = FenwickBitVec::new(len);
+        let n = len.div_ceil(64);
+        fenwick.words[..n].copy_from_slice(&words[..n]);
+        if !len.is_multiple_of(64) {
+            fenwick.words[n - 1] &= (1 << (len % 64)) - 1;
+        }
+        // Counts of blocks, then added up in linear time.
+        for (i, block) in fenwick.words.chunks(BLOCK_WORDS).enumerate() {
+            fenwick.tree[i + 1] = block.iter().fold(0, |acc, w| acc + w.ones());
+        }
+        for i in 1..fenwick.tree.len() {
+            let parent = i + (i & i.wrapping_neg());
+            if parent < fenwick.tree.len() {
+                fenwick.tree[parent] += fenwick.tree[i];
+            }
+        }
+        fenwick
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Count non-zero bits.
+    pub fn ones(&self) -> usize {
+        self.prefix(self.tree.len() - 1)
+    }
+
+    /// Count zero bits.
+    pub fn zeros(&self) -> usize {
+        self.len - self.ones()
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "index out of bounds");
+        self.words[i / 64] & 1 << (i % 64) != 0
+    }
+
+    /// Return true if the 'i+1'th bit was not set.
+    pub fn set(&mut self, i: usize) -> bool {
+        assert!(i < self.len, "index out of bounds");
+        let mask = 1 << (i % 64);
+        let word = &mut self.words[i / 64];
+        if *word & mask != 0 {
+            return false;
+        }
+        *word |= mask;
+        self.add(i / BLOCK_BITS, |count| count + 1);
+        true
+    }
+
+    /// Return true if the 'i+1'th bit was set.
+    pub fn clear(&mut self, i: usize) -> bool {
+        assert!(i < self.len, "index out of bounds");
+        let mask = 1 << (i % 64);
+        let word = &mut self.words[i / 64];
+        if *word & mask == 0 {
+            return false;
+        }
+        *word &= !mask;
+        self.add(i / BLOCK_BITS, |count| count - 1);
+        true
+    }
+
+    // Update counts of nodes covering `block`.
+    fn add<F: Fn(usize) -> usize>(&mut self, block: usize, f: F) {
+        let mut i = block + 1;
+        while i < self.tree.len() {
+            self.tree[i] = f(self.tree[i]);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    // Ones in blocks before `block`.
+    fn prefix(&self, block: usize) -> usize {
+        let mut ones = 0;
+        let mut i = block;
+        while i > 0 {
+            ones += self.tree[i];
+            i &= i - 1;
+        }
+        ones
+    }
+
+    // Block of the 'c+1'th one, or zero if not `one`, and the count before it.
+    fn search(&self, c: usize, one: bool) -> (usize, usize) {
+        let blocks = self.tree.len() - 1;
+        let (mut block, mut before) = (0, 0);
+        let mut step = if blocks == 0 { 0 } else { 1 << (63 - (blocks as u64).leading_zeros()) };
+        while step > 0 {
+            let next = block + step;
+            if next <= blocks {
+                let ones = self.tree[next];
+                let count = if one { ones } else { step * BLOCK_BITS - ones };
+                if before + count <= c {
+                    block = next;
+                    before += count;
+                }
+            }
+            step >>= 1;
+        }
+        (block, before)
+    }
+
+    fn select(&self, c: usize, one: bool) -> usize {
+        let (block, before) = self.search(c, one);
+        let mut c = c - before;
+        let start = block * BLOCK_WORDS;
+        for (i, &w) in self.words[start..start + BLOCK_WORDS].iter().enumerate() {
+            let w = if one { w } else { !w };
+            let ones = w.ones();
+            if c < ones {
+                let p: usize = w.select1(c).unwrap();
+                return (start + i) * 64 + p;
+            }
+            c -= ones;
+        }
+        unreachable!("select out of block")
+    }
+}
+
+impl Bits for FenwickBitVec {
+    const SIZE: usize = usize::MAX;
+
+    fn none() -> Self {
+        FenwickBitVec::default()
+    }
+
+    fn ones(&self) -> usize {
+        FenwickBitVec::ones(self)
+    }
+
+    fn zeros(&self) -> usize {
+        FenwickBitVec::zeros(self)
+    }
+}
+
+impl BitLen for FenwickBitVec {
+    fn len(&self) -> usize {
+        FenwickBitVec::len(self)
+    }
+
+    fn ones(&self) -> usize {
+        FenwickBitVec::ones(self)
+    }
+
+    fn zeros(&self) -> usize {
+        FenwickBitVec::zeros(self)
+    }
+}
+
+impl Rank for FenwickBitVec {
+    fn rank1(&self, i: usize) -> usize {
+        if i >= self.len {
+            return self.ones();
+        }
+        let (q, r) = (i / 64, i % 64);
+        let block = i / BLOCK_BITS;
+        let rank: usize = self.words[q].rank1(r);
+        let words = &self.words[block * BLOCK_WORDS..q];
+        words.iter().fold(self.prefix(block) + rank, |acc, w| acc + w.ones())
+    }
+
+    fn rank0(&self, i: usize) -> usize {
+        i.min(self.len) - self.rank1(i)
+    }
+}
+
+impl Select1 for FenwickBitVec {
+    fn select1(&self, c: usize) -> Option<usize> {
+        if c < self.ones() { Some(self.select(c, true)) } else { None }
+    }
+}
+
+impl Select0 for FenwickBitVec {
+    fn select0(&self, c: usize) -> Option<usize> {
+        if c < self.zeros() { Some(self.select(c, false)) } else { None }
+    }
+}
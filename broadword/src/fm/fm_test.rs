@@ -0,0 +1,78 @@
+extern crate rand;
+use self::rand::Rng;
+
+use super::*;
+
+fn naive_suffix_array(text: &[u8]) -> Vec<usize> {
+    // The sentinel sorts first, so a suffix sorts before any of its extensions.
+    let mut sa = (0..=text.len()).collect::<Vec<usize>>();
+    sa.sort_by(|&a, &b| text[a..].cmp(&text[b..]));
+    sa
+}
+
+fn naive_locate(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+    if pattern.len() > text.len() {
+        return Vec::new();
+    }
+    (0..=text.len() - pattern.len()).filter(|&i| &text[i..i + pattern.len()] == pattern).collect()
+}
+
+fn gen<R: Rng>(len: usize, sigma: u8, rng: &mut R) -> Vec<u8> {
+    // Repeats, so that LMS substrings are equal and SA-IS recurses.
+    let mut text = Vec::with_capacity(len);
+    while text.len() < len {
+        if text.len() > 10 && rng.gen_weighted_bool(3) {
+            let start = rng.gen_range(0, text.len() - 5);
+            let end = rng.gen_range(start, text.len());
+            let piece = text[start..end].to_vec();
+            text.extend(piece);
+        } else {
+            text.push(rng.gen_range(0, sigma));
+        }
+    }
+    text.truncate(len);
+    text
+}
+
+#[test]
+fn fm_suffix_array() {
+    let mut rng = rand::thread_rng();
+    for text in [&b""[..], b"a", b"aaaaaaaa", b"banana", b"mississippi", b"\0\0\x01\0"] {
+        assert_eq!(suffix_array(text), naive_suffix_array(text));
+    }
+    for &len in &[10, 100, 2000] {
+        for &sigma in &[1, 2, 4, 255] {
+            let text = gen(len, sigma, &mut rng);
+            assert_eq!(suffix_array(&text), naive_suffix_array(&text));
+        }
+    }
+}
+
+#[test]
+fn fm_count_locate_extract() {
+    let mut rng = rand::thread_rng();
+    for &len in &[0, 1, 50, 3000] {
+        for &sigma in &[1, 3, 255] {
+            let text = gen(len, sigma, &mut rng);
+            for &rate in &[1, 4, 32] {
+                let fm = FmIndex::with_sample_rate(&text, rate);
+                assert_eq!(fm.len(), len);
+                assert_eq!(fm.extract(0..len), text);
+                for _ in 0..50 {
+                    let start = rng.gen_range(0, len + 1);
+                    let end = rng.gen_range(start, (start + 20).min(len) + 1);
+                    assert_eq!(fm.extract(start..end), &text[start..end]);
+
+                    let pattern = &text[start..end];
+                    let positions = naive_locate(&text, pattern);
+                    assert_eq!(fm.count(pattern), positions.len());
+                    assert_eq!(fm.locate(pattern), positions);
+
+                    let mut absent = pattern.to_vec();
+                    absent.push(rng.gen());
+                    assert_eq!(fm.locate(&absent), naive_locate(&text, &absent));
+                }
+            }
+        }
+    }
+}
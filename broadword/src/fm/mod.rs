@@ -0,0 +1,164 @@
+//! Compressed full-text index over bytes.
+//!
+//! Ferragina and Manzini, "Opportunistic data structures with applications", FOCS 2000.
+
+use core::ops::Range;
+use alloc::vec::Vec;
+
+use super::bitvec::BitVec;
+use super::wavelet::WaveletMatrix;
+use super::Rank;
+
+mod sais;
+use self::sais::suffix_array;
+
+#[cfg(test)]
+mod fm_test;
+
+const SAMPLE_RATE: usize = 32;
+
+/// Index of a text for counting and locating its substrings, which also stores the text.
+///
+/// Rows are the suffixes of the text and a sentinel, in sorted order.
+/// The text is kept as the Burrows-Wheeler transform in a `WaveletMatrix`,
+/// plus the suffix array sampled every `rate` text positions.
+#[derive(Clone, Debug)]
+pub struct FmIndex {
+    len: usize,
+    // Byte before the suffix of each row, 0 for the row of the whole text.
+    bwt: WaveletMatrix,
+    // Row of the whole text.
+    dollar: usize,
+    // `counts[c]` is the count of rows starting with a byte less than `c`, or the sentinel.
+    counts: Vec<usize>,
+    rate: usize,
+    // Rows whose suffix starts at a multiple of `rate`, and that multiple divided by `rate`.
+    sampled: BitVec,
+    positions: Vec<usize>,
+    // Row of each suffix starting at a multiple of `rate`.
+    rows: Vec<usize>,
+}
+
+impl FmIndex {
+    pub fn new(text: &[u8]) -> FmIndex {
+        FmIndex::with_sample_rate(text, SAMPLE_RATE)
+    }
+
+    /// Sample every `rate` positions, which trades space for the speed of `locate` and `extract`.
+    pub fn with_sample_rate(text: &[u8], rate: usize) -> FmIndex {
+        assert!(rate > 0, "sample rate must be positive");
+        let sa = suffix_array(text);
+        let mut dollar = 0;
+        let mut bwt = Vec::with_capacity(sa.len());
+        for (row, &i) in sa.iter().enumerate() {
+            if i == 0 {
+                dollar = row;
+                bwt.push(0);
+            } else {
+                bwt.push(text[i - 1]);
+            }
+        }
+
+        let mut counts = vec![0; 257];
+        for &c in text {
+            counts[c as usize + 1] += 1;
+        }
+        counts[0] = 1;
+        for c in 1..counts.len() {
+            counts[c] += counts[c - 1];
+        }
+
+        let sampled = sa.iter().map(|&i| i % rate == 0).collect::<BitVec>();
+        let positions = sa.iter().filter(|&&i| i % rate == 0).map(|&i| i / rate).collect();
+        let mut rows = vec![0; text.len() / rate + 1];
+        for (row, &i) in sa.iter().enumerate() {
+            if i % rate == 0 {
+                rows[i / rate] = row;
+            }
+        }
+
+        let bwt = WaveletMatrix::new(&bwt);
+        FmIndex { len: text.len(), bwt, dollar, counts, rate, sampled, positions, rows }
+    }
+
+    /// Length of the text.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Count occurrences of `c` in the transform before `row`.
+    fn rank(&self, c: u8, row: usize) -> usize {
+        let rank = self.bwt.rank(c, row);
+        if c == 0 && self.dollar < row { rank - 1 } else { rank }
+    }
+
+    // Byte before the suffix of `row`, and the row of the suffix starting with it.
+    // REQUIRES: row != self.dollar
+    fn lf(&self, row: usize) -> (u8, usize) {
+        let c = self.bwt.get(row);
+        (c, self.counts[c as usize] + self.rank(c, row))
+    }
+
+    // Rows whose suffix starts with `pattern`, all of them if it is empty.
+    fn range(&self, pattern: &[u8]) -> Range<usize> {
+        let (mut start, mut end) = (0, self.len + 1);
+        for &c in pattern.iter().rev() {
+            start = self.counts[c as usize] + self.rank(c, start);
+            end = self.counts[c as usize] + self.rank(c, end);
+            if start >= end {
+                return 0..0;
+            }
+        }
+        start..end
+    }
+
+    /// Count occurrences of `pattern` in the text,
+    /// where the empty pattern occurs at every position and at the end.
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.range(pattern).len()
+    }
+
+    /// Positions of occurrences of `pattern` in the text, in ascending order.
+    pub fn locate(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut positions = self.range(pattern).map(|row| self.position(row)).collect::<Vec<_>>();
+        positions.sort_unstable();
+        positions
+    }
+
+    // Text position of the suffix of `row`, walking back to a sampled one.
+    fn position(&self, row: usize) -> usize {
+        let (mut row, mut steps) = (row, 0);
+        while !self.sampled.get(row) {
+            row = self.lf(row).1;
+            steps += 1;
+        }
+        self.positions[self.sampled.rank1(row)] * self.rate + steps
+    }
+
+    /// Bytes of the text in `range`.
+    pub fn extract(&self, range: Range<usize>) -> Vec<u8> {
+        assert!(range.start <= range.end && range.end <= self.len, "range out of bounds");
+        // Walk back from the first sampled position at or after the end.
+        let k = range.end.div_ceil(self.rate);
+        let (mut pos, mut row) = if k * self.rate <= self.len {
+            (k * self.rate, self.rows[k])
+        } else {
+            (self.len, 0)
+        };
+        let mut bytes = Vec::with_capacity(range.len());
+        while pos > range.start {
+            let (c, prev) = self.lf(row);
+            pos -= 1;
+            if pos < range.end {
+                bytes.push(c);
+            }
+            row = prev;
+        }
+        bytes.reverse();
+        bytes
+    }
+}
@@ -0,0 +1,138 @@
+//! Suffix array construction by induced sorting.
+//!
+//! Nong, Zhang and Chan, "Two efficient algorithms for linear time suffix array construction", 2011.
+
+use alloc::vec::Vec;
+
+const EMPTY: usize = usize::MAX;
+
+/// Suffix array of `text`, which is followed by an implicit sentinel smaller than any byte.
+/// The first entry is `text.len()`, the suffix of the sentinel alone.
+pub fn suffix_array(text: &[u8]) -> Vec<usize> {
+    let mut s = text.iter().map(|&c| c as u32 + 1).collect::<Vec<u32>>();
+    s.push(0);
+    sais(&s, 257)
+}
+
+// REQUIRES: `s` ends with 0, which occurs nowhere else, and is less than `k`.
+fn sais(s: &[u32], k: usize) -> Vec<usize> {
+    let n = s.len();
+    if n == 1 {
+        return vec![0];
+    }
+    // S-type suffixes are smaller than the next one.
+    let mut stype = vec![false; n];
+    stype[n - 1] = true;
+    for i in (0..n - 1).rev() {
+        stype[i] = s[i] < s[i + 1] || (s[i] == s[i + 1] && stype[i + 1]);
+    }
+    let is_lms = |i: usize| i > 0 && stype[i] && !stype[i - 1];
+
+    let mut counts = vec![0; k];
+    for &c in s {
+        counts[c as usize] += 1;
+    }
+
+    // Sort LMS substrings by inducing from LMS suffixes in text order.
+    let lms = (1..n).filter(|&i| is_lms(i)).collect::<Vec<usize>>();
+    let mut sa = vec![EMPTY; n];
+    induce(s, &stype, &counts, &lms, &mut sa);
+
+    // Name LMS substrings by rank, equal ones sharing a name.
+    let mut names = vec![EMPTY; n];
+    let mut name = 0;
+    let mut prev = EMPTY;
+    for &i in sa.iter().filter(|&&i| i != EMPTY && is_lms(i)) {
+        if prev != EMPTY && !lms_equal(s, &stype, prev, i) {
+            name += 1;
+        }
+        names[i] = name;
+        prev = i;
+    }
+
+    // Sort LMS suffixes by recursing on the names, unless all are distinct.
+    let reduced = lms.iter().map(|&i| names[i] as u32).collect::<Vec<u32>>();
+    let order = if name + 1 == lms.len() {
+        let mut order = vec![0; lms.len()];
+        for (j, &r) in reduced.iter().enumerate() {
+            order[r as usize] = j;
+        }
+        order
+    } else {
+        sais(&reduced, name + 1)
+    };
+    let sorted = order.iter().map(|&j| lms[j]).collect::<Vec<usize>>();
+    induce(s, &stype, &counts, &sorted, &mut sa);
+    sa
+}
+
+// Place `lms` at the ends of their buckets, in order, then induce L-type and S-type suffixes.
+fn induce(s: &[u32], stype: &[bool], counts: &[usize], lms: &[usize], sa: &mut [usize]) {
+    let ends = |tails: &mut Vec<usize>| {
+        tails.clear();
+        let mut sum = 0;
+        for &count in counts {
+            sum += count;
+            tails.push(sum);
+        }
+    };
+    let mut bucket = Vec::with_capacity(counts.len());
+
+    sa.fill(EMPTY);
+    ends(&mut bucket);
+    for &i in lms.iter().rev() {
+        let c = s[i] as usize;
+        bucket[c] -= 1;
+        sa[bucket[c]] = i;
+    }
+
+    // Bucket heads.
+    bucket.clear();
+    let mut sum = 0;
+    for &count in counts {
+        bucket.push(sum);
+        sum += count;
+    }
+    for j in 0..sa.len() {
+        let i = sa[j];
+        if i != EMPTY && i > 0 && !stype[i - 1] {
+            let c = s[i - 1] as usize;
+            sa[bucket[c]] = i - 1;
+            bucket[c] += 1;
+        }
+    }
+
+    ends(&mut bucket);
+    for j in (0..sa.len()).rev() {
+        let i = sa[j];
+        if i != EMPTY && i > 0 && stype[i - 1] {
+            let c = s[i - 1] as usize;
+            bucket[c] -= 1;
+            sa[bucket[c]] = i - 1;
+        }
+    }
+}
+
+// Whether LMS substrings at `a` and `b` are equal, in characters and types.
+fn lms_equal(s: &[u32], stype: &[bool], a: usize, b: usize) -> bool {
+    let n = s.len();
+    if a == n - 1 || b == n - 1 {
+        return a == b;
+    }
+    let is_lms = |i: usize| stype[i] && !stype[i - 1];
+    let mut j = 0;
+    loop {
+        // The sentinel is unique, so neither goes past it.
+        if s[a + j] != s[b + j] || stype[a + j] != stype[b + j] {
+            return false;
+        }
+        if j > 0 {
+            match (is_lms(a + j), is_lms(b + j)) {
+                (true, true) => return true,
+                (false, false) => {}
+                _ => return false,
+            }
+        }
+        j += 1;
+    }
+}
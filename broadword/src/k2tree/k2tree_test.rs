@@ -0,0 +1,79 @@
+extern crate rand;
+use self::rand::Rng;
+
+use std::collections::BTreeSet;
+
+use super::*;
+
+fn gen<R: Rng>(len: usize, count: usize, rng: &mut R) -> Vec<(usize, usize)> {
+    if len == 0 {
+        return Vec::new();
+    }
+    (0..count).map(|_| (rng.gen_range(0, len), rng.gen_range(0, len))).collect()
+}
+
+fn check<const K: usize>(len: usize, edges: &[(usize, usize)]) {
+    let mut rng = rand::thread_rng();
+    let set = edges.iter().cloned().collect::<BTreeSet<(usize, usize)>>();
+    let tree = K2Tree::<K>::new(len, edges);
+    assert_eq!(tree.len(), len);
+    assert_eq!(tree.edges(), set.len());
+
+    for u in 0..len {
+        let out = set.range((u, 0)..(u + 1, 0)).map(|&(_, v)| v).collect::<Vec<usize>>();
+        assert_eq!(tree.neighbors(u), out);
+        let into = set.iter().filter(|&&(_, v)| v == u).map(|&(u, _)| u).collect::<Vec<usize>>();
+        assert_eq!(tree.reverse_neighbors(u), into);
+    }
+    for &(u, v) in edges {
+        assert!(tree.has_edge(u, v));
+    }
+    for _ in 0..200.min(len * len) {
+        let (u, v) = (rng.gen_range(0, len), rng.gen_range(0, len));
+        assert_eq!(tree.has_edge(u, v), set.contains(&(u, v)));
+    }
+    for _ in 0..50 {
+        let (a, b) = (rng.gen_range(0, len + 1), rng.gen_range(0, len + 1));
+        let (c, d) = (rng.gen_range(0, len + 1), rng.gen_range(0, len + 1));
+        let (rows, cols) = (a.min(b)..a.max(b), c.min(d)..c.max(d));
+        let mut found = tree.range(rows.clone(), cols.clone());
+        found.sort_unstable();
+        let expected = set
+            .iter()
+            .filter(|&&(u, v)| rows.contains(&u) && cols.contains(&v))
+            .cloned()
+            .collect::<Vec<(usize, usize)>>();
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn k2tree_random() {
+    let mut rng = rand::thread_rng();
+    for &len in &[0, 1, 2, 5, 64, 100, 1000] {
+        for &count in &[0, 1, len, 10 * len] {
+            let edges = gen(len, count, &mut rng);
+            check::<2>(len, &edges);
+            check::<4>(len, &edges);
+        }
+    }
+}
+
+#[test]
+fn k2tree_clustered() {
+    // A clique and a path, which leave most submatrices empty.
+    let mut edges = Vec::new();
+    for u in 0..32 {
+        for v in 0..32 {
+            edges.push((u, v));
+        }
+    }
+    for u in 500..999 {
+        edges.push((u, u + 1));
+    }
+    check::<2>(1000, &edges);
+    check::<3>(1000, &edges);
+
+    let tree = K2Tree::<2>::new(1 << 16, &edges);
+    assert!(tree.size_in_bytes() < edges.len() * 8);
+}
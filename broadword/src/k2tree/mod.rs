@@ -0,0 +1,179 @@
+//! Compressed adjacency matrix of a static graph.
+//!
+//! Brisaboa, Ladra and Navarro, "k2-trees for compact web graph representation", SPIRE 2009.
+
+use core::ops::Range;
+use alloc::vec::Vec;
+
+use super::bitvec::BitVec;
+use super::Rank;
+
+#[cfg(test)]
+mod k2tree_test;
+
+/// Adjacency matrix of `len` nodes split into `K` by `K` submatrices, recursively.
+///
+/// Each level holds a bit for each submatrix of the nonempty ones above it,
+/// in row-major order within each, so that the children of the 'r+1'th one
+/// start at `r * K * K` on the level below, where `r` counts ones on all levels.
+/// The last level holds the cells of the matrix.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct K2Tree<const K: usize = 2> {
+    len: usize,
+    // Side of the matrix, a power of `K`.
+    side: usize,
+    // All levels but the last.
+    tree: BitVec,
+    leaves: BitVec,
+}
+
+impl<const K: usize> Default for K2Tree<K> {
+    fn default() -> K2Tree<K> {
+        K2Tree::new(0, &[])
+    }
+}
+
+impl<const K: usize> K2Tree<K> {
+    const CHECK: () = assert!(K >= 2, "arity must be at least 2");
+
+    /// Build from the edges of a graph of `len` nodes, duplicates are ignored.
+    pub fn new(len: usize, edges: &[(usize, usize)]) -> K2Tree<K> {
+        let () = Self::CHECK;
+        assert!(edges.iter().all(|&(u, v)| u < len && v < len), "node out of bounds");
+        let mut side = K;
+        while side < len {
+            side *= K;
+        }
+
+        // Submatrices of a level, by their edges relative to their corner.
+        let mut level = vec![edges.to_vec()];
+        let mut tree = Vec::new();
+        let mut bits = Vec::new();
+        let mut size = side;
+        while size > 1 {
+            tree.append(&mut bits);
+            size /= K;
+            let mut next = Vec::new();
+            for edges in level {
+                let mut children = vec![Vec::new(); K * K];
+                for (u, v) in edges {
+                    children[u / size * K + v / size].push((u % size, v % size));
+                }
+                for child in children {
+                    bits.push(!child.is_empty());
+                    if !child.is_empty() {
+                        next.push(child);
+                    }
+                }
+            }
+            level = next;
+        }
+        let tree = tree.into_iter().collect();
+        let leaves = bits.into_iter().collect();
+        K2Tree { len, side, tree, leaves }
+    }
+
+    /// Count of nodes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Count of edges.
+    pub fn edges(&self) -> usize {
+        self.leaves.ones()
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.tree.size_in_bytes() + self.leaves.size_in_bytes()
+    }
+
+    fn get(&self, p: usize) -> bool {
+        if p < self.tree.len() { self.tree.get(p) } else { self.leaves.get(p - self.tree.len()) }
+    }
+
+    // Start of the children of the one at `p`.
+    fn children(&self, p: usize) -> usize {
+        self.tree.rank1(p + 1) * K * K
+    }
+
+    pub fn has_edge(&self, u: usize, v: usize) -> bool {
+        assert!(u < self.len && v < self.len, "node out of bounds");
+        let (mut u, mut v) = (u, v);
+        let (mut start, mut size) = (0, self.side);
+        loop {
+            size /= K;
+            let p = start + u / size * K + v / size;
+            if !self.get(p) {
+                return false;
+            }
+            if size == 1 {
+                return true;
+            }
+            u %= size;
+            v %= size;
+            start = self.children(p);
+        }
+    }
+
+    /// Nodes with an edge from `u`, in ascending order.
+    pub fn neighbors(&self, u: usize) -> Vec<usize> {
+        assert!(u < self.len, "node out of bounds");
+        self.range(u..u + 1, 0..self.len).into_iter().map(|(_, v)| v).collect()
+    }
+
+    /// Nodes with an edge to `v`, in ascending order.
+    pub fn reverse_neighbors(&self, v: usize) -> Vec<usize> {
+        assert!(v < self.len, "node out of bounds");
+        self.range(0..self.len, v..v + 1).into_iter().map(|(u, _)| u).collect()
+    }
+
+    /// Edges from a node in `rows` to one in `cols`, in the order of the tree.
+    pub fn range(&self, rows: Range<usize>, cols: Range<usize>) -> Vec<(usize, usize)> {
+        let rows = rows.start..rows.end.min(self.len);
+        let cols = cols.start..cols.end.min(self.len);
+        let mut edges = Vec::new();
+        if !rows.is_empty() && !cols.is_empty() {
+            self.collect(0, self.side, (0, 0), &rows, &cols, &mut edges);
+        }
+        edges
+    }
+
+    // Edges under the submatrix whose children start at `start`,
+    // which has sides of `size` and its corner at `corner`.
+    fn collect(
+        &self,
+        start: usize,
+        size: usize,
+        corner: (usize, usize),
+        rows: &Range<usize>,
+        cols: &Range<usize>,
+        edges: &mut Vec<(usize, usize)>,
+    ) {
+        let size = size / K;
+        for i in 0..K {
+            let u = corner.0 + i * size;
+            if u + size <= rows.start || rows.end <= u {
+                continue;
+            }
+            for j in 0..K {
+                let v = corner.1 + j * size;
+                if v + size <= cols.start || cols.end <= v {
+                    continue;
+                }
+                let p = start + i * K + j;
+                if !self.get(p) {
+                    continue;
+                }
+                if size == 1 {
+                    edges.push((u, v));
+                } else {
+                    self.collect(self.children(p), size, (u, v), rows, cols, edges);
+                }
+            }
+        }
+    }
+}
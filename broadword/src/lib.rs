@@ -2,18 +2,65 @@
 //! Springer Berlin Heidelberg, 2008. 154-168.
 //!
 //! `http://sux.di.unimi.it/paper.pdf`
+//!
+//! Word level traits need only `core`, containers need `alloc`.
+//! The default `std` feature adds serialization and runtime CPU feature detection.
 
-#![feature(associated_consts)]
-#![feature(test)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(all(test, feature = "nightly"), feature(test))]
+// Reference patterns (`&Repr::Vec(ones, ref bits)`) and explicit lifetimes are used throughout.
+#![allow(clippy::match_ref_pats, clippy::needless_borrowed_reference, clippy::needless_lifetimes)]
 
+#[cfg(all(test, feature = "nightly"))]
 extern crate test;
 
+// `core` is in the crate root only if `no_std`.
+#[cfg(any(feature = "std", test))]
+extern crate core;
+#[cfg_attr(not(any(feature = "std", test)), macro_use)]
+extern crate alloc;
+
+mod bitbuf;
 mod repr;
 mod bucket;
+mod bitmap;
+mod bsi;
+mod expr;
+mod ef;
+mod rrr;
+mod dynamic;
+mod fenwick;
+mod bitvec;
+mod wavelet;
+mod fm;
+mod louds;
+mod rmq;
+mod k2tree;
+mod qf;
+mod pattern;
+
+pub use repr::Repr;
+pub use bitmap::{Bitmap, Bitmap64, SparseArray};
+#[cfg(target_has_atomic = "64")]
+pub use bitmap::ConcurrentBitmap;
+pub use bsi::BitSlicedIndex;
+pub use expr::{Expr, Provider};
+pub use ef::{EliasFano, PartitionedEliasFano};
+pub use rrr::{Rrr, Rrr15, Rrr63};
+pub use dynamic::DynamicBitVec;
+pub use fenwick::FenwickBitVec;
+pub use bitvec::BitVec;
+pub use wavelet::WaveletMatrix;
+pub use fm::FmIndex;
+pub use louds::LoudsTrie;
+pub use rmq::Rmq;
+pub use k2tree::K2Tree;
+pub use qf::QuotientFilter;
+pub use pattern::{Myers, ShiftAnd, ShiftOr};
 
 // Constant sized bits.
 pub trait Bits {
-    /// Size of this representation.
+    /// Size of this representation, an upper bound if sized at runtime.
     const SIZE: usize;
 
     /// The value with all bits unset.
@@ -31,6 +78,26 @@ pub trait Bits {
         Self::SIZE - self.ones()
     }
 }
+// Bits of a size known at runtime, such as bit vectors,
+// whose `Bits::SIZE` is only an upper bound.
+pub trait BitLen {
+    /// Count of bits.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Count non-zero bits.
+    fn ones(&self) -> usize {
+        self.len() - self.zeros()
+    }
+
+    /// Count zero bits.
+    fn zeros(&self) -> usize {
+        self.len() - self.ones()
+    }
+}
 pub trait Rank<T = usize>: Bits {
     /// Count how many non-zero bits there are up to a given position
     fn rank1(&self, i: usize) -> T;
@@ -58,7 +125,7 @@ macro_rules! impl_sizedbits {
         }
     )*)
 }
-impl_sizedbits!((u64, 64), (u32, 32), (u16, 16), (u8, 8));
+impl_sizedbits!((u128, 128), (u64, 64), (u32, 32), (u16, 16), (u8, 8));
 #[cfg(target_pointer_width = "32")]
 impl_sizedbits!{(usize, 32)}
 #[cfg(target_pointer_width = "64")]
@@ -100,7 +167,7 @@ macro_rules! impl_rank9_all {
         impl_rank9!(($type, u64), ($type, u32), ($type, u16), ($type, u8), ($type, usize));
     )*)
 }
-impl_rank9_all!(u64, u32, u16, u8, usize);
+impl_rank9_all!(u128, u64, u32, u16, u8, usize);
 
 const X01: u64 = 0x0101010101010101;
 const X02: u64 = 0x2020202020202020;
@@ -112,30 +179,23 @@ const X0F: u64 = 0x0f0f0f0f0f0f0f0f;
 const X55: u64 = X22 + X33 + X22 + X33;
 const X8X: u64 = X81 + X80 + X80 + X80;
 
+// Narrower words are zero extended to u64, and `select0` inverts bits before
+// widening, so positions at or above `Self::SIZE` are never selected.
 macro_rules! impl_select9 {
     ( $( ($type: ty, $out: ty) ),* ) => ($(
         impl Select1<$out> for $type {
             #[inline]
             fn select1(&self, c: usize) -> Option<$out> {
-                let x = *self as u64;
-                let s0 = x - ((x & X55) >> 1);
-                let s1 = (s0 & X33) + ((s0 >> 2) & X33);
-                let s2 = ((s1 + (s1 >> 4)) & X0F).wrapping_mul(X01);
-                let p0 = (le8(s2, (c as u64 * X01)) >> 7).wrapping_mul(X01);
-                let p1 = (p0 >> 53) & !0x7;
-                let p2 = p1 as u32;
-                let p3 = (s2 << 8).wrapping_shr(p2);
-                let p4 = c - (p3 & 0xFF) as usize;
-                let p5 = lt8(0x0, ((x.wrapping_shr(p2) & 0xFF) * X01) & X8X);
-                let s3 = (p5 >> 0x7).wrapping_mul(X01);
-                let p6 = (le8(s3, (p4 as u64 * X01)) >> 7).wrapping_mul(X01) >> 56;
-                let p = p1 + p6;
+                let p = select1(*self as u64, c);
                 if p >= Self::SIZE as u64 { None } else { Some(p as $out) }
             }
         }
         impl Select0<$out> for $type {
             #[inline]
-            fn select0(&self, c: usize) -> Option<$out> { (!*self).select1(c) }
+            fn select0(&self, c: usize) -> Option<$out> {
+                let this: $type = !*self;
+                this.select1(c)
+            }
         }
     )*)
 }
@@ -146,6 +206,148 @@ macro_rules! impl_select9_all {
 }
 impl_select9_all!(u64, u32, u16, u8, usize);
 
+// Select in the low word first, then in the high word.
+macro_rules! impl_select9_u128 {
+    ( $( $out: ty ),* ) => ($(
+        impl Select1<$out> for u128 {
+            #[inline]
+            fn select1(&self, c: usize) -> Option<$out> {
+                let lo = *self as u64;
+                let ones = lo.ones();
+                let p = if c < ones {
+                    select1(lo, c)
+                } else {
+                    64 + select1((*self >> 64) as u64, c - ones)
+                };
+                if p >= Self::SIZE as u64 { None } else { Some(p as $out) }
+            }
+        }
+        impl Select0<$out> for u128 {
+            #[inline]
+            fn select0(&self, c: usize) -> Option<$out> { (!*self).select1(c) }
+        }
+    )*)
+}
+impl_select9_u128!(u64, u32, u16, u8, usize);
+
+// Fixed size blocks of words, e.g. `[u64; 8]` for a cache line.
+impl<const N: usize> Bits for [u64; N] {
+    const SIZE: usize = 64 * N;
+    #[inline]
+    fn none() -> Self {
+        [0; N]
+    }
+    #[inline]
+    fn ones(&self) -> usize {
+        self.iter().fold(0, |acc, w| acc + w.ones())
+    }
+}
+
+macro_rules! impl_words {
+    ( $( $out: ty ),* ) => ($(
+        impl<const N: usize> Rank<$out> for [u64; N] {
+            #[inline]
+            fn rank1(&self, i: usize) -> $out {
+                let q = i / 64;
+                let r = i % 64;
+                let rank = if q >= N {
+                    self.ones()
+                } else {
+                    let rank: usize = self[q].rank1(r);
+                    self[..q].iter().fold(rank, |acc, w| acc + w.ones())
+                };
+                rank as $out
+            }
+            #[inline]
+            fn rank0(&self, i: usize) -> $out {
+                let rank1: $out = self.rank1(i);
+                i as $out - rank1
+            }
+        }
+        impl<const N: usize> Select1<$out> for [u64; N] {
+            #[inline]
+            fn select1(&self, c: usize) -> Option<$out> {
+                let mut r = c;
+                for (i, w) in self.iter().enumerate() {
+                    let ones = w.ones();
+                    if r < ones {
+                        return Some((64 * i as u64 + select1(*w, r)) as $out);
+                    }
+                    r -= ones;
+                }
+                None
+            }
+        }
+        impl<const N: usize> Select0<$out> for [u64; N] {
+            #[inline]
+            fn select0(&self, c: usize) -> Option<$out> {
+                let mut r = c;
+                for (i, w) in self.iter().enumerate() {
+                    let zeros = w.zeros();
+                    if r < zeros {
+                        return Some((64 * i as u64 + select1(!*w, r)) as $out);
+                    }
+                    r -= zeros;
+                }
+                None
+            }
+        }
+    )*)
+}
+impl_words!(u64, u32, u16, u8, usize);
+
+// Return the 'c+1'th non-zero bit's index of `x`, 64 or greater if not found.
+#[inline]
+fn select1(x: u64, c: usize) -> u64 {
+    #[cfg(all(target_arch = "x86_64", feature = "std"))]
+    {
+        if is_x86_feature_detected!("bmi2") {
+            return unsafe { select1_bmi2(x, c) };
+        }
+    }
+    // Without `std`, BMI2 is used only if the target enables it.
+    #[cfg(all(target_arch = "x86_64", not(feature = "std"), target_feature = "bmi2"))]
+    {
+        return unsafe { select1_bmi2(x, c) };
+    }
+    #[allow(unreachable_code)]
+    select1_broadword(x, c)
+}
+
+// Comparisons are done in 8 bits lanes, so 'c' must be checked beforehand.
+#[inline]
+fn select1_broadword(x: u64, c: usize) -> u64 {
+    if c >= 64 {
+        return 64;
+    }
+    let s0 = x - ((x & X55) >> 1);
+    let s1 = (s0 & X33) + ((s0 >> 2) & X33);
+    let s2 = ((s1 + (s1 >> 4)) & X0F).wrapping_mul(X01);
+    let p0 = (le8(s2, c as u64 * X01) >> 7).wrapping_mul(X01);
+    let p1 = (p0 >> 53) & !0x7;
+    let p2 = p1 as u32;
+    let p3 = (s2 << 8).wrapping_shr(p2);
+    let p4 = c - (p3 & 0xFF) as usize;
+    let p5 = lt8(0x0, ((x.wrapping_shr(p2) & 0xFF) * X01) & X8X);
+    let s3 = (p5 >> 0x7).wrapping_mul(X01);
+    let p6 = (le8(s3, p4 as u64 * X01) >> 7).wrapping_mul(X01) >> 56;
+    p1 + p6
+}
+
+// PDEP deposits `1 << c` to the 'c+1'th non-zero bit of `x`,
+// TZCNT of the result is 64 if `x` has no such bit.
+#[cfg(target_arch = "x86_64")]
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+#[target_feature(enable = "bmi2")]
+#[inline]
+unsafe fn select1_bmi2(x: u64, c: usize) -> u64 {
+    use core::arch::x86_64::{_pdep_u64, _tzcnt_u64};
+    if c >= 64 {
+        return 64;
+    }
+    _tzcnt_u64(_pdep_u64(1 << c, x))
+}
+
 fn le8(x: u64, y: u64) -> u64 {
     let x8 = X02 + X02 + X02 + X02;
     let xs = (y | x8) - (x & !x8);
@@ -160,6 +362,11 @@ fn lt8(x: u64, y: u64) -> u64 {
 
 #[cfg(test)]
 mod tests {
+    extern crate rand;
+    use self::rand::Rng;
+    #[cfg(feature = "nightly")]
+    use test::Bencher;
+
     use super::*;
 
     struct Test(u64, (usize, Option<usize>));
@@ -194,4 +401,168 @@ mod tests {
             }
         }
     }
+
+    // None if BMI2 is not available.
+    #[cfg(target_arch = "x86_64")]
+    fn try_select1_bmi2(x: u64, c: usize) -> Option<u64> {
+        if is_x86_feature_detected!("bmi2") {
+            Some(unsafe { select1_bmi2(x, c) })
+        } else {
+            None
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    fn try_select1_bmi2(_: u64, _: usize) -> Option<u64> {
+        None
+    }
+
+    fn naive_select1(x: u64, c: usize) -> u64 {
+        (0..64).filter(|&i| x & (1 << i) != 0).nth(c).unwrap_or(64)
+    }
+
+    #[test]
+    fn select1_bmi2_and_broadword() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..10000 {
+            // Vary density, so that every 'c' is likely to be tested.
+            let x = rng.gen::<u64>() & rng.gen::<u64>() | rng.gen::<u64>() & rng.gen::<u64>();
+            let ones = x.count_ones() as usize;
+            for c in 0..ones {
+                let want = naive_select1(x, c);
+                assert_eq!(select1_broadword(x, c), want, "{:b} {}", x, c);
+                if let Some(p) = try_select1_bmi2(x, c) {
+                    assert_eq!(p, want, "{:b} {}", x, c);
+                }
+            }
+            assert!(select1_broadword(x, ones) >= 64);
+            assert!(try_select1_bmi2(x, ones).is_none_or(|p| p >= 64));
+        }
+    }
+
+    macro_rules! select_exhaustive {
+        ( $type: ty ) => {
+            let size = <$type as Bits>::SIZE;
+            let mut cs = (0..size + 2).collect::<Vec<usize>>();
+            cs.extend_from_slice(&[127, 128, 255, 256, 1000, usize::MAX]);
+
+            let mut x: $type = 0;
+            loop {
+                let ones = (0..size).filter(|&i| x & (1 << i) != 0).collect::<Vec<_>>();
+                let zeros = (0..size).filter(|&i| x & (1 << i) == 0).collect::<Vec<_>>();
+                for &c in &cs {
+                    let s1: Option<usize> = x.select1(c);
+                    let s0: Option<usize> = x.select0(c);
+                    assert_eq!(s1, ones.get(c).cloned(), "{:b}.select1({})", x, c);
+                    assert_eq!(s0, zeros.get(c).cloned(), "{:b}.select0({})", x, c);
+
+                    // Both kernels must agree with the oracle for zero extended words.
+                    let want = ones.get(c).map_or(64, |&p| p as u64);
+                    assert_eq!(select1_broadword(x as u64, c).min(64), want);
+                    if let Some(p) = try_select1_bmi2(x as u64, c) {
+                        assert_eq!(p.min(64), want);
+                    }
+                }
+                if x == <$type>::MAX {
+                    break;
+                }
+                x += 1;
+            }
+        };
+    }
+
+    #[test]
+    fn select_exhaustive_u8() {
+        select_exhaustive!(u8);
+    }
+
+    #[test]
+    fn select_exhaustive_u16() {
+        select_exhaustive!(u16);
+    }
+
+    macro_rules! rank_select_oracle {
+        ( $bits: expr, $size: expr, $test: expr ) => {
+            let bits = $bits;
+            let test = $test;
+            let ones = (0..$size).filter(|&i| test(&bits, i)).collect::<Vec<usize>>();
+            let zeros = (0..$size).filter(|&i| !test(&bits, i)).collect::<Vec<usize>>();
+            assert_eq!(bits.ones(), ones.len());
+            for i in 0..$size + 2 {
+                let r1: usize = bits.rank1(i);
+                let r0: usize = bits.rank0(i);
+                assert_eq!(r1, ones.iter().filter(|&&p| p < i).count());
+                assert_eq!(r0, i - r1);
+
+                let s1: Option<usize> = bits.select1(i);
+                let s0: Option<usize> = bits.select0(i);
+                assert_eq!(s1, ones.get(i).cloned(), "select1({})", i);
+                assert_eq!(s0, zeros.get(i).cloned(), "select0({})", i);
+            }
+        };
+    }
+
+    #[test]
+    fn u128_rank_select() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let x = rng.gen::<u64>() as u128 | (rng.gen::<u64>() as u128) << 64;
+            let x = if rng.gen() { x & rng.gen::<u64>() as u128 } else { x };
+            rank_select_oracle!(x, 128, |x: &u128, i| x & (1u128 << i) != 0);
+        }
+    }
+
+    #[test]
+    fn words_rank_select() {
+        fn test<const N: usize>(x: &[u64; N], i: usize) -> bool {
+            x[i / 64] & (1 << (i % 64)) != 0
+        }
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let mut x2 = [0u64; 2];
+            let mut x8 = [0u64; 8];
+            for w in x2.iter_mut().chain(x8.iter_mut()) {
+                *w = if rng.gen() { rng.gen() } else { rng.gen::<u64>() & rng.gen::<u64>() };
+            }
+            rank_select_oracle!(x2, 128, test);
+            rank_select_oracle!(x8, 512, test);
+
+            // Positions in two words fit in a byte, as for u128.
+            let i = rng.gen_range(0, 129);
+            let r: u8 = x2.rank1(i);
+            assert_eq!(r as usize, Rank::<usize>::rank1(&x2, i));
+            let c = rng.gen_range(0, 129);
+            let s: Option<u8> = x2.select0(c);
+            assert_eq!(s.map(|p| p as usize), Select0::<usize>::select0(&x2, c));
+        }
+        rank_select_oracle!(<[u64; 4]>::none(), 256, test);
+        rank_select_oracle!([!0u64; 4], 256, test);
+    }
+
+    #[cfg(feature = "nightly")]
+    fn bench_words() -> Vec<(u64, usize)> {
+        let mut rng = rand::thread_rng();
+        (0..1024)
+            .map(|_| {
+                     let x = rng.gen::<u64>();
+                     (x, rng.gen_range(0, x.count_ones() as usize))
+                 })
+            .collect()
+    }
+
+    #[cfg(feature = "nightly")]
+    #[bench]
+    fn select1_broadword_bench(bench: &mut Bencher) {
+        let words = bench_words();
+        bench.iter(|| words.iter().fold(0, |acc, &(x, c)| acc ^ select1_broadword(x, c)));
+    }
+
+    #[cfg(all(target_arch = "x86_64", feature = "nightly"))]
+    #[bench]
+    fn select1_bmi2_bench(bench: &mut Bencher) {
+        if !is_x86_feature_detected!("bmi2") {
+            return;
+        }
+        let words = bench_words();
+        bench.iter(|| words.iter().fold(0, |acc, &(x, c)| acc ^ unsafe { select1_bmi2(x, c) }));
+    }
 }
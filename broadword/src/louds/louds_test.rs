@@ -0,0 +1,71 @@
+extern crate rand;
+use self::rand::Rng;
+
+use std::collections::BTreeSet;
+
+use super::*;
+
+fn gen<R: Rng>(size: usize, rng: &mut R) -> BTreeSet<Vec<u8>> {
+    let mut keys = BTreeSet::new();
+    for _ in 0..size {
+        // Few distinct bytes, so that keys share prefixes.
+        let len = rng.gen_range(0, 8);
+        keys.insert((0..len).map(|_| b'a' + rng.gen_range(0, 4)).collect());
+    }
+    keys
+}
+
+#[test]
+fn louds_lookup() {
+    let mut rng = rand::thread_rng();
+    for &size in &[0, 1, 10, 2000] {
+        let set = gen(size, &mut rng);
+        let keys = set.iter().cloned().collect::<Vec<Vec<u8>>>();
+        let trie = LoudsTrie::from_sorted_slice(&keys);
+        assert_eq!(trie.len(), keys.len());
+
+        let mut ids = BTreeSet::new();
+        for key in &keys {
+            let id = trie.lookup(key).unwrap();
+            assert!(id < keys.len());
+            assert!(ids.insert(id));
+            assert_eq!(trie.key(id).as_ref(), Some(key));
+        }
+        assert_eq!(trie.key(keys.len()), None);
+        for key in gen(200, &mut rng) {
+            assert_eq!(trie.contains(&key), set.contains(&key));
+        }
+    }
+}
+
+#[test]
+fn louds_prefixes_predictive() {
+    let mut rng = rand::thread_rng();
+    let set = gen(3000, &mut rng);
+    let keys = set.iter().cloned().collect::<Vec<Vec<u8>>>();
+    // Duplicates are ignored.
+    let mut dups = keys.clone();
+    dups.extend(keys.iter().cloned().step_by(3));
+    dups.sort();
+    let trie = LoudsTrie::from_sorted_slice(&dups);
+    assert_eq!(trie.len(), keys.len());
+
+    let all = trie.iter().map(|(id, key)| {
+        assert_eq!(trie.lookup(&key), Some(id));
+        key
+    });
+    assert_eq!(all.collect::<Vec<_>>(), keys);
+
+    for query in gen(200, &mut rng) {
+        let prefixes = trie.prefixes(&query).map(|(id, len)| {
+            assert_eq!(trie.key(id).unwrap(), &query[..len]);
+            len
+        });
+        let expected = (0..=query.len()).filter(|&len| set.contains(&query[..len]));
+        assert_eq!(prefixes.collect::<Vec<_>>(), expected.collect::<Vec<_>>());
+
+        let predictive = trie.predictive(&query).map(|(_, key)| key);
+        let expected = keys.iter().filter(|key| key.starts_with(&query)).cloned();
+        assert_eq!(predictive.collect::<Vec<_>>(), expected.collect::<Vec<_>>());
+    }
+}
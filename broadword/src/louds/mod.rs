@@ -0,0 +1,194 @@
+//! Static trie of byte strings in level-order unary degree sequence.
+//!
+//! Jacobson, "Space-efficient static trees and graphs", FOCS 1989.
+
+use core::ops::Range;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use super::bitvec::BitVec;
+use super::{Rank, Select0, Select1};
+
+#[cfg(test)]
+mod louds_test;
+
+/// Trie of a static set of keys, each mapped to an id in `0..len`.
+///
+/// Nodes are numbered in breadth-first order, the root being 0.
+/// The shape is stored as the degree of each node in unary, after "10" for a super root,
+/// so that the 'v+1'th one stands for node `v` and its children follow the 'v+1'th zero.
+/// Ids are ranks of the nodes ending a key, so they follow breadth-first order too.
+#[derive(Clone, Debug)]
+pub struct LoudsTrie {
+    louds: BitVec,
+    // Label of the edge into each node but the root.
+    labels: Vec<u8>,
+    // Nodes ending a key.
+    terminal: BitVec,
+}
+
+impl Default for LoudsTrie {
+    fn default() -> LoudsTrie {
+        LoudsTrie::from_sorted_slice::<&[u8]>(&[])
+    }
+}
+
+impl LoudsTrie {
+    /// Build from ascending keys, duplicates are ignored.
+    pub fn from_sorted_slice<K: AsRef<[u8]>>(keys: &[K]) -> LoudsTrie {
+        debug_assert!(keys.windows(2).all(|w| w[0].as_ref() <= w[1].as_ref()));
+        let mut louds = vec![true, false];
+        let mut labels = Vec::new();
+        let mut terminal = Vec::new();
+
+        // Each node is the range of keys sharing its prefix of `depth` bytes.
+        let mut queue = VecDeque::new();
+        queue.push_back((0, keys.len(), 0));
+        while let Some((lo, hi, depth)) = queue.pop_front() {
+            let mut i = lo;
+            while i < hi && keys[i].as_ref().len() == depth {
+                i += 1;
+            }
+            terminal.push(i > lo);
+            while i < hi {
+                let label = keys[i].as_ref()[depth];
+                let len = keys[i..hi].iter().position(|k| k.as_ref()[depth] != label);
+                let end = i + len.unwrap_or(hi - i);
+                louds.push(true);
+                labels.push(label);
+                queue.push_back((i, end, depth + 1));
+                i = end;
+            }
+            louds.push(false);
+        }
+        let louds = louds.into_iter().collect();
+        let terminal = terminal.into_iter().collect();
+        LoudsTrie { louds, labels, terminal }
+    }
+
+    /// Count of keys.
+    pub fn len(&self) -> usize {
+        self.terminal.ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Children of `v`, which are consecutive.
+    fn children(&self, v: usize) -> Range<usize> {
+        let start = self.louds.select0(v).unwrap();
+        let end = self.louds.select0(v + 1).unwrap();
+        let first = start - v;
+        first..first + (end - start - 1)
+    }
+
+    fn parent(&self, v: usize) -> usize {
+        self.louds.rank0(self.louds.select1(v).unwrap()) - 1
+    }
+
+    fn label(&self, v: usize) -> u8 {
+        self.labels[v - 1]
+    }
+
+    fn child(&self, v: usize, label: u8) -> Option<usize> {
+        let children = self.children(v);
+        let labels = &self.labels[children.start - 1..children.end - 1];
+        labels.binary_search(&label).ok().map(|i| children.start + i)
+    }
+
+    // Node of `prefix`.
+    fn find(&self, prefix: &[u8]) -> Option<usize> {
+        prefix.iter().try_fold(0, |v, &label| self.child(v, label))
+    }
+
+    fn id(&self, v: usize) -> Option<usize> {
+        if self.terminal.get(v) { Some(self.terminal.rank1(v)) } else { None }
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.lookup(key).is_some()
+    }
+
+    /// Return the id of `key`.
+    pub fn lookup(&self, key: &[u8]) -> Option<usize> {
+        self.find(key).and_then(|v| self.id(v))
+    }
+
+    /// Return the key of `id`.
+    pub fn key(&self, id: usize) -> Option<Vec<u8>> {
+        let mut v = self.terminal.select1(id)?;
+        let mut key = Vec::new();
+        while v != 0 {
+            key.push(self.label(v));
+            v = self.parent(v);
+        }
+        key.reverse();
+        Some(key)
+    }
+
+    /// Ids and lengths of keys which are prefixes of `query`, shortest first.
+    pub fn prefixes<'a>(&'a self, query: &'a [u8]) -> Prefixes<'a> {
+        Prefixes { trie: self, query, node: Some(0), depth: 0 }
+    }
+
+    /// Ids and keys starting with `prefix`, in ascending order of keys.
+    pub fn predictive(&self, prefix: &[u8]) -> Predictive<'_> {
+        let start = self.find(prefix);
+        let stack = start.map_or(Vec::new(), |v| vec![(v, prefix.len())]);
+        Predictive { trie: self, start: start.unwrap_or(0), stack, key: prefix.to_vec() }
+    }
+
+    /// Ids and keys in ascending order of keys.
+    pub fn iter(&self) -> Predictive<'_> {
+        self.predictive(&[])
+    }
+}
+
+pub struct Prefixes<'a> {
+    trie: &'a LoudsTrie,
+    query: &'a [u8],
+    node: Option<usize>,
+    depth: usize,
+}
+
+impl<'a> Iterator for Prefixes<'a> {
+    type Item = (usize, usize);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let v = self.node?;
+            let depth = self.depth;
+            self.node = self.query.get(depth).and_then(|&label| self.trie.child(v, label));
+            self.depth += 1;
+            if let Some(id) = self.trie.id(v) {
+                return Some((id, depth));
+            }
+        }
+    }
+}
+
+pub struct Predictive<'a> {
+    trie: &'a LoudsTrie,
+    start: usize,
+    // Nodes to visit, with the length of their key.
+    stack: Vec<(usize, usize)>,
+    key: Vec<u8>,
+}
+
+impl<'a> Iterator for Predictive<'a> {
+    type Item = (usize, Vec<u8>);
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((v, depth)) = self.stack.pop() {
+            if v != self.start {
+                self.key.truncate(depth - 1);
+                self.key.push(self.trie.label(v));
+            }
+            // Pushed in reverse, so that the smallest label is visited first.
+            self.stack.extend(self.trie.children(v).rev().map(|c| (c, depth + 1)));
+            if let Some(id) = self.trie.id(v) {
+                return Some((id, self.key.clone()));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,48 @@
+//! Bit-parallel exact and approximate matching of byte strings.
+//!
+//! Baeza-Yates and Gonnet, "A new approach to text searching", CACM 1992;
+//! Myers, "A fast bit-vector algorithm for approximate string matching
+//! based on dynamic programming", JACM 1999;
+//! Hyyrö, "A bit-vector algorithm for computing Levenshtein and Damerau edit distances", 2003.
+
+use alloc::vec::Vec;
+
+use super::Bits;
+
+mod shift;
+pub use self::shift::{ShiftAnd, ShiftOr};
+
+mod myers;
+pub use self::myers::Myers;
+
+#[cfg(test)]
+mod pattern_test;
+
+// Count of pattern positions per word.
+const W: usize = <u64 as Bits>::SIZE;
+
+// Masks of the positions of each byte in `pattern`, `words` words per byte,
+// where positions past the end are set if `pad`.
+fn masks(pattern: &[u8], words: usize, pad: bool) -> Vec<u64> {
+    let mut masks = vec![0; 256 * words];
+    for (i, &c) in pattern.iter().enumerate() {
+        masks[c as usize * words + i / W] |= 1 << (i % W);
+    }
+    if pad && !pattern.len().is_multiple_of(W) {
+        let tail = !0 << (pattern.len() % W);
+        for c in 0..256 {
+            masks[c * words + words - 1] |= tail;
+        }
+    }
+    masks
+}
+
+// Shift `words` up by one bit across words, shifting in `bit`.
+fn shl1(words: &mut [u64], bit: u64) {
+    let mut carry = bit;
+    for word in words {
+        let next = *word >> (W - 1);
+        *word = *word << 1 | carry;
+        carry = next;
+    }
+}
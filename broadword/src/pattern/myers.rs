@@ -0,0 +1,148 @@
+use alloc::vec::Vec;
+
+use super::{masks, W};
+
+/// Pattern for edit distance and approximate search, in blocks of 64 pattern positions.
+///
+/// Each column of the table of distances between prefixes of the pattern and of the text
+/// is kept as the differences down the column, +1 and -1 each in a word per block,
+/// and all positions of a block are advanced to the next column in a few word operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Myers {
+    len: usize,
+    words: usize,
+    // Positions of each byte in the pattern, padded with matches.
+    peq: Vec<u64>,
+}
+
+// Advance the differences of a block by a column, given the difference above its first row,
+// and return the difference at the row of `high`.
+fn step(pv: &mut u64, mv: &mut u64, eq: u64, hin: isize, high: u64) -> isize {
+    let (p, m) = (*pv, *mv);
+    let xv = eq | m;
+    let eq = if hin < 0 { eq | 1 } else { eq };
+    let xh = ((eq & p).wrapping_add(p) ^ p) | eq;
+    let mut ph = m | !(xh | p);
+    let mut mh = p & xh;
+    let hout = if ph & high != 0 { 1 } else if mh & high != 0 { -1 } else { 0 };
+    ph <<= 1;
+    mh <<= 1;
+    if hin < 0 {
+        mh |= 1;
+    } else if hin > 0 {
+        ph |= 1;
+    }
+    *pv = mh | !(xv | ph);
+    *mv = ph & xv;
+    hout
+}
+
+// Columns of a pattern against a text.
+struct Table {
+    pv: Vec<u64>,
+    mv: Vec<u64>,
+    // Distance at the last row of each block, or at the last row of the pattern.
+    score: Vec<usize>,
+}
+
+impl Myers {
+    pub fn new(pattern: &[u8]) -> Myers {
+        let words = pattern.len().div_ceil(W).max(1);
+        Myers { len: pattern.len(), words, peq: masks(pattern, words, true) }
+    }
+
+    /// Length of the pattern.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Last row of block `b`, counting from 1.
+    fn bottom(&self, b: usize) -> usize {
+        if b + 1 == self.words { self.len } else { (b + 1) * W }
+    }
+
+    fn high(&self, b: usize) -> u64 {
+        1 << ((self.bottom(b) - 1) % W)
+    }
+
+    // Distances of the first column, from the empty prefix of the text.
+    fn table(&self) -> Table {
+        let score = (0..self.words).map(|b| self.bottom(b)).collect();
+        Table { pv: vec![!0; self.words], mv: vec![0; self.words], score }
+    }
+
+    // Advance blocks `blocks` to the column of `c`, given the difference above the first.
+    fn column(&self, table: &mut Table, blocks: (usize, usize), c: u8, hin: isize) {
+        let mut hin = hin;
+        for b in blocks.0..=blocks.1 {
+            let eq = self.peq[c as usize * self.words + b];
+            let hout = step(&mut table.pv[b], &mut table.mv[b], eq, hin, self.high(b));
+            table.score[b] = (table.score[b] as isize + hout) as usize;
+            hin = hout;
+        }
+    }
+
+    /// Levenshtein distance between the pattern and `text`.
+    pub fn distance(&self, text: &[u8]) -> usize {
+        self.distance_within(text, self.len.max(text.len())).unwrap()
+    }
+
+    /// Levenshtein distance between the pattern and `text`, if at most `k`.
+    ///
+    /// Only the blocks within `k` of the diagonal are advanced, so this takes O(k n / 64).
+    pub fn distance_within(&self, text: &[u8], k: usize) -> Option<usize> {
+        if self.len.abs_diff(text.len()) > k {
+            return None;
+        }
+        if self.len == 0 {
+            return Some(text.len());
+        }
+        let mut table = self.table();
+        let (mut first, mut last) = (0, 0);
+        for (j, &c) in text.iter().enumerate() {
+            let j = j + 1;
+            // Blocks entering the band start from distances above them plus one for each row,
+            // and those leaving it are taken as one more above the next,
+            // which may only overestimate distances greater than `k`.
+            while last + 1 < self.words && (last + 1) * W < j + k {
+                last += 1;
+                table.pv[last] = !0;
+                table.mv[last] = 0;
+                let rows = self.bottom(last) - self.bottom(last - 1);
+                table.score[last] = table.score[last - 1] + rows;
+            }
+            while first < last && (first + 1) * W + k < j {
+                first += 1;
+            }
+            self.column(&mut table, (first, last), c, 1);
+        }
+        let distance = table.score[self.words - 1];
+        if distance <= k { Some(distance) } else { None }
+    }
+
+    /// Ends of substrings of `text` within distance `k` of the pattern,
+    /// with the least distance of those ending there, in ascending order.
+    pub fn find(&self, text: &[u8], k: usize) -> Vec<(usize, usize)> {
+        if self.len == 0 {
+            return (0..=text.len()).map(|j| (j, 0)).collect();
+        }
+        let mut found = Vec::new();
+        if self.len <= k {
+            found.push((0, self.len));
+        }
+        let mut table = self.table();
+        for (j, &c) in text.iter().enumerate() {
+            // A match may start anywhere, so the first row stays zero.
+            self.column(&mut table, (0, self.words - 1), c, 0);
+            let distance = table.score[self.words - 1];
+            if distance <= k {
+                found.push((j + 1, distance));
+            }
+        }
+        found
+    }
+}
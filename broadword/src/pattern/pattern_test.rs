@@ -0,0 +1,119 @@
+extern crate rand;
+use self::rand::Rng;
+
+use super::*;
+
+fn gen<R: Rng>(len: usize, rng: &mut R) -> Vec<u8> {
+    // Few distinct bytes, so that there are many matches.
+    (0..len).map(|_| b'a' + rng.gen_range(0, 3)).collect()
+}
+
+fn levenshtein(a: &[u8], b: &[u8]) -> usize {
+    let mut row = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, &x) in a.iter().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, &y) in b.iter().enumerate() {
+            let next = (diag + (x != y) as usize).min(row[j] + 1).min(row[j + 1] + 1);
+            diag = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+// Least distance of the pattern to a substring of `text` ending at each position.
+fn ends(pattern: &[u8], text: &[u8]) -> Vec<usize> {
+    let mut col = (0..=pattern.len()).collect::<Vec<usize>>();
+    let mut ends = vec![pattern.len()];
+    for &y in text {
+        let mut diag = col[0];
+        for (i, &x) in pattern.iter().enumerate() {
+            let next = (diag + (x != y) as usize).min(col[i] + 1).min(col[i + 1] + 1);
+            diag = col[i + 1];
+            col[i + 1] = next;
+        }
+        ends.push(col[pattern.len()]);
+    }
+    ends
+}
+
+static LENS: &[usize] = &[0, 1, 2, 5, 63, 64, 65, 130];
+
+#[test]
+fn shift_find() {
+    let mut rng = rand::thread_rng();
+    for &m in LENS {
+        let pattern = gen(m, &mut rng);
+        let mut text = gen(1000, &mut rng);
+        // Plant some occurrences.
+        for _ in 0..3 {
+            if m <= text.len() {
+                let at = rng.gen_range(0, text.len() - m + 1);
+                text[at..at + m].copy_from_slice(&pattern);
+            }
+        }
+        let starts = (0..=text.len().saturating_sub(m))
+            .filter(|&i| i + m <= text.len() && text[i..i + m] == pattern[..])
+            .collect::<Vec<usize>>();
+        assert_eq!(ShiftAnd::new(&pattern).find(&text), starts);
+        assert_eq!(ShiftOr::new(&pattern).find(&text), starts);
+
+        let shift = ShiftAnd::new(&pattern);
+        assert_eq!(shift.len(), m);
+        for &k in &[0, 1, 3, m] {
+            let want = (0..=text.len().saturating_sub(m))
+                .filter(|&i| i + m <= text.len())
+                .map(|i| (i, text[i..i + m].iter().zip(&pattern).filter(|&(x, y)| x != y).count()))
+                .filter(|&(_, d)| d <= k)
+                .collect::<Vec<(usize, usize)>>();
+            assert_eq!(shift.find_mismatches(&text, k), want, "m = {}, k = {}", m, k);
+        }
+    }
+}
+
+#[test]
+fn myers_distance() {
+    let mut rng = rand::thread_rng();
+    for &m in LENS {
+        for &n in LENS {
+            let pattern = gen(m, &mut rng);
+            let myers = Myers::new(&pattern);
+            // Similar texts, and random ones.
+            let mut similar = pattern.clone();
+            for _ in 0..rng.gen_range(0, 10) {
+                let i = rng.gen_range(0, similar.len() + 1);
+                match rng.gen_range(0, 3) {
+                    0 => similar.insert(i, b'a'),
+                    1 if i < similar.len() => drop(similar.remove(i)),
+                    _ if i < similar.len() => similar[i] = b'c',
+                    _ => {}
+                }
+            }
+            for text in &[gen(n, &mut rng), similar] {
+                let d = levenshtein(&pattern, text);
+                assert_eq!(myers.distance(text), d, "{:?} {:?}", pattern, text);
+                for &k in &[0, 1, 2, 5, 20, 70, 200] {
+                    let want = if d <= k { Some(d) } else { None };
+                    assert_eq!(myers.distance_within(text, k), want, "k = {}", k);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn myers_find() {
+    let mut rng = rand::thread_rng();
+    for &m in LENS {
+        let pattern = gen(m, &mut rng);
+        let text = gen(500, &mut rng);
+        let myers = Myers::new(&pattern);
+        let ends = ends(&pattern, &text);
+        for &k in &[0, 1, 3, m / 2, m] {
+            let want = ends.iter().cloned().enumerate().filter(|&(_, d)| d <= k);
+            let want = want.collect::<Vec<(usize, usize)>>();
+            assert_eq!(myers.find(&text, k), want, "m = {}, k = {}", m, k);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use core::mem;
+use alloc::vec::Vec;
+
+use super::{masks, shl1, W};
+
+/// Pattern for exact and k-mismatch search, one bit of state per pattern position.
+///
+/// Bit `i` of the state after a byte of the text is set
+/// if the first `i + 1` bytes of the pattern end at that byte.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShiftAnd {
+    len: usize,
+    words: usize,
+    // Positions of each byte in the pattern.
+    masks: Vec<u64>,
+}
+
+impl ShiftAnd {
+    pub fn new(pattern: &[u8]) -> ShiftAnd {
+        let words = pattern.len().div_ceil(W).max(1);
+        ShiftAnd { len: pattern.len(), words, masks: masks(pattern, words, false) }
+    }
+
+    /// Length of the pattern.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn mask(&self, c: u8) -> &[u64] {
+        let i = c as usize * self.words;
+        &self.masks[i..i + self.words]
+    }
+
+    fn matched(&self, state: &[u64]) -> bool {
+        let last = self.len - 1;
+        state[last / W] & 1 << (last % W) != 0
+    }
+
+    /// Start positions of occurrences in `text`, in ascending order.
+    pub fn find(&self, text: &[u8]) -> Vec<usize> {
+        self.find_mismatches(text, 0).into_iter().map(|(start, _)| start).collect()
+    }
+
+    /// Start positions of substrings of `text` differing from the pattern in at most `k` bytes,
+    /// with the count of those bytes, in ascending order.
+    pub fn find_mismatches(&self, text: &[u8], k: usize) -> Vec<(usize, usize)> {
+        if self.len == 0 {
+            return (0..=text.len()).map(|i| (i, 0)).collect();
+        }
+        // `states[d]` is the state with up to `d` mismatches.
+        let k = k.min(self.len);
+        let mut states = vec![0; (k + 1) * self.words];
+        let (mut prev, mut cur) = (vec![0; self.words], vec![0; self.words]);
+        let mut found = Vec::new();
+        for (j, &c) in text.iter().enumerate() {
+            let mask = self.mask(c);
+            for (d, state) in states.chunks_mut(self.words).enumerate() {
+                // Matching here, or not on top of `d - 1` mismatches before.
+                let mut carry = 1;
+                for i in 0..self.words {
+                    cur[i] = state[i] << 1 | carry;
+                    carry = state[i] >> (W - 1);
+                    state[i] = cur[i] & mask[i] | if d > 0 { prev[i] } else { 0 };
+                }
+                mem::swap(&mut prev, &mut cur);
+            }
+            if j + 1 >= self.len {
+                let d = states.chunks(self.words).position(|state| self.matched(state));
+                if let Some(d) = d {
+                    found.push((j + 1 - self.len, d));
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Pattern for exact search, with the state of `ShiftAnd` complemented,
+/// so that shifting in a zero starts a match for free.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShiftOr {
+    len: usize,
+    words: usize,
+    // Positions of other bytes in the pattern.
+    masks: Vec<u64>,
+}
+
+impl ShiftOr {
+    pub fn new(pattern: &[u8]) -> ShiftOr {
+        let words = pattern.len().div_ceil(W).max(1);
+        let masks = masks(pattern, words, false).into_iter().map(|m| !m).collect();
+        ShiftOr { len: pattern.len(), words, masks }
+    }
+
+    /// Length of the pattern.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Start positions of occurrences in `text`, in ascending order.
+    pub fn find(&self, text: &[u8]) -> Vec<usize> {
+        if self.len == 0 {
+            return (0..=text.len()).collect();
+        }
+        let last = self.len - 1;
+        let mut state = vec![!0; self.words];
+        let mut found = Vec::new();
+        for (j, &c) in text.iter().enumerate() {
+            let i = c as usize * self.words;
+            shl1(&mut state, 0);
+            for (s, &m) in state.iter_mut().zip(&self.masks[i..i + self.words]) {
+                *s |= m;
+            }
+            if state[last / W] & 1 << (last % W) == 0 {
+                found.push(j + 1 - self.len);
+            }
+        }
+        found
+    }
+}
@@ -0,0 +1,340 @@
+//! Approximate multiset of hashes, which supports deletion and merging.
+//!
+//! Pandey, Bender, Johnson and Patro, "A general-purpose counting filter:
+//! making every bit count", SIGMOD 2017.
+
+use core::mem;
+use alloc::vec::Vec;
+
+use super::bitbuf::BitBuf;
+use super::{Rank, Select1};
+
+#[cfg(test)]
+mod qf_test;
+
+// Count of slots per block.
+const SLOTS: usize = 64;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Block {
+    // Count of slots from the start of this block which hold runs of earlier quotients.
+    offset: usize,
+    // Quotients of some fingerprint, and slots ending a run.
+    occupieds: u64,
+    runends: u64,
+}
+
+/// Rank-and-select quotient filter of `u64` hashes.
+///
+/// The low `q + r` bits of a hash are its fingerprint, of which the high `q` bits
+/// are its quotient, the home slot out of `2^q`, and the low `r` bits are its remainder.
+/// Remainders of a quotient form a sorted run at or after their home slot,
+/// and runs are stored in the order of their quotients, shifting others as needed.
+/// Each block of 64 slots marks the quotients in use and the ends of runs,
+/// so that the run of a quotient is found by `rank` and `select` on these words.
+///
+/// A hash inserted `k` times is counted `k` times, taking a slot each.
+/// A hash not inserted is counted with a probability of about `len / 2^(q + r)`.
+/// Filters grow when full, taking a bit of the remainder for the quotient.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuotientFilter {
+    qbits: usize,
+    rbits: usize,
+    len: usize,
+    blocks: Vec<Block>,
+    remainders: BitBuf,
+}
+
+impl QuotientFilter {
+    /// Filter of `2^qbits` slots with remainders of `rbits` bits.
+    pub fn new(qbits: usize, rbits: usize) -> QuotientFilter {
+        assert!(qbits > 0 && rbits > 0, "quotient and remainder must be nonempty");
+        assert!(qbits + rbits <= 64 && qbits < 48, "fingerprint too large");
+        // Runs of the last quotients overflow into extra slots.
+        let slots: usize = (1 << qbits) + (8 << (qbits / 2));
+        let blocks = vec![Block::default(); slots.div_ceil(SLOTS)];
+        let mut remainders = BitBuf::default();
+        remainders.push_zeros(blocks.len() * SLOTS * rbits);
+        QuotientFilter { qbits, rbits, len: 0, blocks, remainders }
+    }
+
+    /// Count of hashes, each as many times as inserted.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Count of hashes to hold before growing.
+    pub fn capacity(&self) -> usize {
+        (1 << self.qbits) * 19 / 20
+    }
+
+    pub fn quotient_bits(&self) -> usize {
+        self.qbits
+    }
+
+    pub fn remainder_bits(&self) -> usize {
+        self.rbits
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        self.blocks.len() * mem::size_of::<Block>() + self.remainders.size_in_bytes()
+    }
+
+    fn fingerprint(&self, hash: u64) -> u64 {
+        let bits = self.qbits + self.rbits;
+        if bits < 64 { hash & ((1 << bits) - 1) } else { hash }
+    }
+
+    fn split(&self, fingerprint: u64) -> (usize, u64) {
+        let quotient = (fingerprint >> self.rbits) as usize;
+        (quotient, fingerprint & ((1 << self.rbits) - 1))
+    }
+
+    fn slots(&self) -> usize {
+        self.blocks.len() * SLOTS
+    }
+
+    fn occupied(&self, x: usize) -> bool {
+        self.blocks[x / SLOTS].occupieds & 1 << (x % SLOTS) != 0
+    }
+
+    fn runend(&self, x: usize) -> bool {
+        self.blocks[x / SLOTS].runends & 1 << (x % SLOTS) != 0
+    }
+
+    fn set_occupied(&mut self, x: usize, bit: bool) {
+        let block = &mut self.blocks[x / SLOTS];
+        block.occupieds = block.occupieds & !(1 << (x % SLOTS)) | (bit as u64) << (x % SLOTS);
+    }
+
+    fn set_runend(&mut self, x: usize, bit: bool) {
+        let block = &mut self.blocks[x / SLOTS];
+        block.runends = block.runends & !(1 << (x % SLOTS)) | (bit as u64) << (x % SLOTS);
+    }
+
+    fn remainder(&self, x: usize) -> u64 {
+        self.remainders.get_bits(x * self.rbits, self.rbits)
+    }
+
+    fn set_remainder(&mut self, x: usize, remainder: u64) {
+        self.remainders.set_bits(x * self.rbits, remainder, self.rbits);
+    }
+
+    // Slot after the runs of quotients up to `x`,
+    // or some slot not after `x` if they all end before it.
+    fn end(&self, x: usize) -> usize {
+        let block = &self.blocks[x / SLOTS];
+        let start = x / SLOTS * SLOTS + block.offset;
+        let d: usize = block.occupieds.rank1(x % SLOTS + 1);
+        if d == 0 { start } else { self.select_runend(start, d - 1) + 1 }
+    }
+
+    // Position of the 'c+1'th run end from `from`.
+    fn select_runend(&self, from: usize, c: usize) -> usize {
+        let mut c = c;
+        let mut w = from / SLOTS;
+        let mut word = self.blocks[w].runends & !0 << (from % SLOTS);
+        loop {
+            let ones = word.count_ones() as usize;
+            if c < ones {
+                let p: usize = word.select1(c).unwrap();
+                return w * SLOTS + p;
+            }
+            c -= ones;
+            w += 1;
+            word = self.blocks[w].runends;
+        }
+    }
+
+    // First slot of the run of `q`, if occupied.
+    fn start(&self, q: usize) -> usize {
+        if q == 0 { 0 } else { self.end(q - 1).max(q) }
+    }
+
+    fn used(&self, x: usize) -> bool {
+        self.end(x) > x
+    }
+
+    fn first_unused(&self, x: usize) -> Option<usize> {
+        let mut y = x;
+        while y < self.slots() {
+            let end = self.end(y);
+            if end <= y {
+                return Some(y);
+            }
+            y = end;
+        }
+        None
+    }
+
+    // Least quotient from `x` in use.
+    fn next_occupied(&self, x: usize) -> Option<usize> {
+        let mut w = x / SLOTS;
+        let mut word = self.blocks.get(w)?.occupieds & !0 << (x % SLOTS);
+        while word == 0 {
+            w += 1;
+            word = self.blocks.get(w)?.occupieds;
+        }
+        Some(w * SLOTS + word.trailing_zeros() as usize)
+    }
+
+    // Add `count` to the offsets of blocks starting in `q + 1..=last`.
+    fn shift_offsets(&mut self, q: usize, last: usize, count: isize) {
+        for block in &mut self.blocks[q / SLOTS + 1..=last / SLOTS] {
+            block.offset = (block.offset as isize + count) as usize;
+        }
+    }
+
+    pub fn insert(&mut self, hash: u64) {
+        let fingerprint = self.fingerprint(hash);
+        self.add(fingerprint);
+    }
+
+    fn add(&mut self, fingerprint: u64) {
+        loop {
+            if self.len >= self.capacity() {
+                self.grow();
+                continue;
+            }
+            let (q, remainder) = self.split(fingerprint);
+            let occupied = self.occupied(q);
+            let start = self.start(q);
+            let end = if occupied { self.end(q) } else { start };
+            let p = (start..end).find(|&y| self.remainder(y) > remainder).unwrap_or(end);
+            let n = match self.first_unused(p) {
+                Some(n) => n,
+                None => {
+                    self.grow();
+                    continue;
+                }
+            };
+
+            // Shift slots `p..n` up by one.
+            for y in (p..n).rev() {
+                let (r, runend) = (self.remainder(y), self.runend(y));
+                self.set_remainder(y + 1, r);
+                self.set_runend(y + 1, runend);
+            }
+            self.set_remainder(p, remainder);
+            if !occupied {
+                self.set_occupied(q, true);
+                self.set_runend(p, true);
+            } else if p == end {
+                self.set_runend(p - 1, false);
+                self.set_runend(p, true);
+            } else {
+                self.set_runend(p, false);
+            }
+            self.shift_offsets(q, n, 1);
+            self.len += 1;
+            return;
+        }
+    }
+
+    fn grow(&mut self) {
+        assert!(self.rbits > 1, "filter is full");
+        let qbits = self.qbits + 1;
+        self.resize(qbits);
+    }
+
+    /// Count how many times `hash` was inserted, or more for false positives.
+    pub fn count(&self, hash: u64) -> usize {
+        let (q, remainder) = self.split(self.fingerprint(hash));
+        if !self.occupied(q) {
+            return 0;
+        }
+        (self.start(q)..self.end(q))
+            .map(|y| self.remainder(y))
+            .skip_while(|&r| r < remainder)
+            .take_while(|&r| r == remainder)
+            .count()
+    }
+
+    pub fn contains(&self, hash: u64) -> bool {
+        self.count(hash) > 0
+    }
+
+    /// Remove `hash` once, return whether it was found.
+    pub fn remove(&mut self, hash: u64) -> bool {
+        let (q, remainder) = self.split(self.fingerprint(hash));
+        if !self.occupied(q) {
+            return false;
+        }
+        let (start, end) = (self.start(q), self.end(q));
+        let p = match (start..end).find(|&y| self.remainder(y) == remainder) {
+            Some(p) => p,
+            None => return false,
+        };
+
+        // Following runs move down with it, up to one at its home slot or an unused slot.
+        let (mut last, mut prev) = (end - 1, q);
+        while last + 1 < self.slots() && self.used(last + 1) {
+            let next = self.next_occupied(prev + 1).unwrap();
+            if next > last {
+                break;
+            }
+            last = self.end(next) - 1;
+            prev = next;
+        }
+
+        if start + 1 == end {
+            self.set_occupied(q, false);
+        } else if p + 1 == end {
+            self.set_runend(p - 1, true);
+        }
+        for y in p..last {
+            let (r, runend) = (self.remainder(y + 1), self.runend(y + 1));
+            self.set_remainder(y, r);
+            self.set_runend(y, runend);
+        }
+        self.set_remainder(last, 0);
+        self.set_runend(last, false);
+        self.shift_offsets(q, last, -1);
+        self.len -= 1;
+        true
+    }
+
+    // Fingerprints in ascending order.
+    fn fingerprints(&self) -> Vec<u64> {
+        let mut fingerprints = Vec::with_capacity(self.len);
+        let mut next = 0;
+        let mut q = self.next_occupied(0);
+        while let Some(quotient) = q {
+            let mut y = next.max(quotient);
+            loop {
+                fingerprints.push((quotient as u64) << self.rbits | self.remainder(y));
+                y += 1;
+                if self.runend(y - 1) {
+                    break;
+                }
+            }
+            next = y;
+            q = self.next_occupied(quotient + 1);
+        }
+        fingerprints
+    }
+
+    /// Move hashes to a filter of `2^qbits` slots, or more if they do not fit,
+    /// keeping the bits of fingerprints.
+    pub fn resize(&mut self, qbits: usize) {
+        let bits = self.qbits + self.rbits;
+        assert!(qbits < bits, "remainder must be nonempty");
+        let fingerprints = self.fingerprints();
+        *self = QuotientFilter::new(qbits, bits - qbits);
+        for fingerprint in fingerprints {
+            self.add(fingerprint);
+        }
+    }
+
+    /// Insert all hashes of `that`, which must have fingerprints of as many bits.
+    pub fn merge(&mut self, that: &QuotientFilter) {
+        assert_eq!(self.qbits + self.rbits, that.qbits + that.rbits, "fingerprints differ");
+        for fingerprint in that.fingerprints() {
+            self.add(fingerprint);
+        }
+    }
+}
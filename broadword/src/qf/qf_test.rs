@@ -0,0 +1,96 @@
+extern crate rand;
+use self::rand::Rng;
+
+use std::collections::BTreeMap;
+
+use super::*;
+
+// Count of each fingerprint of `map` in a filter of `bits`.
+fn expected(map: &BTreeMap<u64, usize>, bits: usize, hash: u64) -> usize {
+    let mask = (1 << bits) - 1;
+    map.iter().filter(|&(&h, _)| h & mask == hash & mask).map(|(_, &c)| c).sum()
+}
+
+fn check(qf: &QuotientFilter, map: &BTreeMap<u64, usize>) {
+    let bits = qf.quotient_bits() + qf.remainder_bits();
+    assert_eq!(qf.len(), map.values().sum::<usize>());
+    for &hash in map.keys() {
+        assert_eq!(qf.count(hash), expected(map, bits, hash));
+    }
+    let fingerprints = qf.fingerprints();
+    assert!(fingerprints.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(fingerprints.len(), qf.len());
+}
+
+#[test]
+fn qf_insert_remove() {
+    let mut rng = rand::thread_rng();
+    for &(qbits, rbits) in &[(1, 16), (6, 8), (8, 12), (10, 20)] {
+        // Hashes in a narrow range collide on quotients, and on fingerprints if `rbits` is small.
+        for &range in &[1u64 << 8, 1 << 16, !0] {
+            let mut qf = QuotientFilter::new(qbits, rbits);
+            let mut map = BTreeMap::new();
+            for _ in 0..3000 {
+                let hash = rng.gen_range(0, range);
+                if rng.gen_weighted_bool(3) && !map.is_empty() {
+                    let hash = *map.keys().nth(rng.gen_range(0, map.len())).unwrap();
+                    assert!(qf.remove(hash));
+                    let count = map.get_mut(&hash).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        map.remove(&hash);
+                    }
+                } else {
+                    qf.insert(hash);
+                    *map.entry(hash).or_insert(0) += 1;
+                }
+            }
+            check(&qf, &map);
+            assert_eq!(qf.quotient_bits() + qf.remainder_bits(), qbits + rbits);
+
+            for (hash, count) in map.clone() {
+                for _ in 0..count {
+                    assert!(qf.remove(hash));
+                }
+                map.remove(&hash);
+                assert_eq!(qf.count(hash), expected(&map, qbits + rbits, hash));
+            }
+            assert!(qf.is_empty());
+            assert!(qf.blocks.iter().all(|b| *b == Block::default()));
+        }
+    }
+}
+
+#[test]
+fn qf_false_positives() {
+    let mut rng = rand::thread_rng();
+    let mut qf = QuotientFilter::new(12, 10);
+    for _ in 0..qf.capacity() {
+        qf.insert(rng.gen());
+    }
+    let positives = (0..10_000).filter(|_| qf.contains(rng.gen())).count();
+    // About `len / 2^22` each, less than 1 in 1000.
+    assert!(positives < 30, "{} false positives", positives);
+}
+
+#[test]
+fn qf_resize_merge() {
+    let mut rng = rand::thread_rng();
+    let mut a = QuotientFilter::new(8, 24);
+    let mut b = QuotientFilter::new(10, 22);
+    let mut map = BTreeMap::new();
+    for i in 0..2000 {
+        let hash = rng.gen_range(0, 1 << 20);
+        if i % 2 == 0 { a.insert(hash) } else { b.insert(hash) }
+        *map.entry(hash).or_insert(0) += 1;
+    }
+    a.resize(a.quotient_bits() + 1);
+    assert_eq!(a.quotient_bits(), 12);
+    a.merge(&b);
+    check(&a, &map);
+
+    // Shrinking grows back as needed.
+    a.resize(4);
+    assert_eq!(a.quotient_bits(), 12);
+    check(&a, &map);
+}
@@ -1,12 +1,15 @@
-use std::ops;
-use super::{pair, Bits, Repr};
+use core::ops;
+use super::{Bits, Repr};
+#[cfg(feature = "simd")]
+use super::simd::vec_and;
 
+#[cfg_attr(feature = "simd", allow(unused_macros))]
 macro_rules! intersection {
     ( $iter: ident, $vec0: expr, $vec1: expr ) => {
         let $iter = {
             let i0 = $vec0.iter();
             let i1 = $vec1.iter();
-            pair::intersection(i0, i1)
+            super::pair::intersection(i0, i1)
         };
     };
 }
@@ -17,13 +20,19 @@ macro_rules! clone_intersect_with {
     };
 }
 
+// Intersection of two `Repr::Vec`.
+#[cfg(not(feature = "simd"))]
+fn vec_and(vec0: &Repr, vec1: &Repr) -> Repr {
+    intersection!(iter, vec0, vec1);
+    iter.collect::<Repr>()
+}
+
 impl Repr {
     fn intersect_with(&mut self, that: &Repr) {
         match (self, that) {
             (vec0 @ &mut Repr::Vec(..), vec1 @ &Repr::Vec(..)) => {
-                let repr = vec0.clone();
-                intersection!(iter, repr, vec1);
-                *vec0 = iter.collect::<Repr>();
+                let repr = vec_and(vec0, vec1);
+                *vec0 = repr;
             }
 
             (repr @ &mut Repr::Map(..), &Repr::Vec(..)) => {
@@ -49,6 +58,7 @@ impl Repr {
                     *ones += p.ones();
                     *x = p;
                 }
+                bits0.truncate(bits1.len());
             }
         }
     }
@@ -58,10 +68,7 @@ impl<'a, 'b> ops::BitAnd<&'b Repr> for &'a Repr {
     type Output = Repr;
     fn bitand(self, that: &Repr) -> Self::Output {
         match (self, that) {
-            (vec0 @ &Repr::Vec(..), vec1 @ &Repr::Vec(..)) => {
-                intersection!(iter, vec0, vec1);
-                iter.collect::<Repr>()
-            }
+            (vec0 @ &Repr::Vec(..), vec1 @ &Repr::Vec(..)) => vec_and(vec0, vec1),
             (map @ &Repr::Map(..), vec @ &Repr::Vec(..)) => {
                 clone_intersect_with!(clone, vec, map);
                 clone
@@ -1,12 +1,15 @@
-use std::ops;
-use super::{pair, Bits, Repr};
+use core::ops;
+use super::{Bits, Repr};
+#[cfg(feature = "simd")]
+use super::simd::vec_or;
 
+#[cfg_attr(feature = "simd", allow(unused_macros))]
 macro_rules! union {
     ( $iter: ident, $vec0: expr, $vec1: expr ) => {
         let $iter = {
             let i0 = $vec0.iter();
             let i1 = $vec1.iter();
-            pair::union(i0, i1)
+            super::pair::union(i0, i1)
         };
     };
 }
@@ -17,13 +20,19 @@ macro_rules! clone_union_with {
     };
 }
 
+// Union of two `Repr::Vec`.
+#[cfg(not(feature = "simd"))]
+fn vec_or(vec0: &Repr, vec1: &Repr) -> Repr {
+    union!(iter, vec0, vec1);
+    iter.collect::<Repr>()
+}
+
 impl Repr {
     fn union_with(&mut self, that: &Repr) {
         match (self, that) {
             (vec0 @ &mut Repr::Vec(..), vec1 @ &Repr::Vec(..)) => {
-                let repr = vec0.clone();
-                union!(iter, repr, vec1);
-                *vec0 = iter.collect::<Repr>();
+                let repr = vec_or(vec0, vec1);
+                *vec0 = repr;
             }
 
             (ref mut repr @ &mut Repr::Map(..), &Repr::Vec(_, ref bits)) => {
@@ -32,7 +41,7 @@ impl Repr {
                 }
             }
 
-            (mut repr @ &mut Repr::Vec(..), map_repr @ &Repr::Map(..)) => {
+            (repr @ &mut Repr::Vec(..), map_repr @ &Repr::Map(..)) => {
                 clone_union_with!(clone, map_repr, repr);
                 *repr = clone;
             }
@@ -44,6 +53,13 @@ impl Repr {
                     *ones += p.ones();
                     *x = p;
                 }
+                // Words beyond the shorter one are copied as is.
+                let len = bits0.len();
+                if len < bits1.len() {
+                    bits0.extend_from_slice(&bits1[len..]);
+                }
+                let tail = len.min(bits1.len());
+                *ones += bits0[tail..].iter().fold(0, |acc, w| acc + w.ones());
             }
         }
     }
@@ -53,11 +69,7 @@ impl<'a, 'b> ops::BitOr<&'b Repr> for &'a Repr {
     type Output = Repr;
     fn bitor(self, that: &Repr) -> Self::Output {
         match (self, that) {
-            (vec0 @ &Repr::Vec(..), vec1 @ &Repr::Vec(..)) => {
-                let repr = vec0.clone();
-                union!(iter, repr, vec1);
-                iter.collect::<Repr>()
-            }
+            (vec0 @ &Repr::Vec(..), vec1 @ &Repr::Vec(..)) => vec_or(vec0, vec1),
             (vec @ &Repr::Vec(..), map @ &Repr::Map(..)) => {
                 clone_union_with!(clone, map, vec);
                 clone
@@ -1,4 +1,4 @@
-use std::ops;
+use core::ops;
 use super::{pair, Bits, Repr};
 
 macro_rules! symmetric_difference {
@@ -47,6 +47,13 @@ impl Repr {
                     *ones += p.ones();
                     *x = p;
                 }
+                // Words beyond the shorter one are copied as is.
+                let len = bits0.len();
+                if len < bits1.len() {
+                    bits0.extend_from_slice(&bits1[len..]);
+                }
+                let tail = len.min(bits1.len());
+                *ones += bits0[tail..].iter().fold(0, |acc, w| acc + w.ones());
             }
         }
     }
@@ -0,0 +1,147 @@
+use alloc::vec::Vec;
+use super::Repr;
+
+impl Repr {
+    /// Build from ascending bits, duplicates are ignored.
+    /// Bits out of order are checked for, and built as by `from_unsorted`.
+    pub fn from_sorted_slice(bits: &[u16]) -> Repr {
+        if !bits.is_sorted() {
+            return Repr::from_unsorted(bits);
+        }
+        let ones = distinct(bits);
+        if ones <= Repr::VEC_SIZE {
+            let mut vec = Vec::with_capacity(ones);
+            vec.extend_from_slice(bits);
+            vec.dedup();
+            Repr::Vec(ones, vec)
+        } else {
+            let mut map = Vec::new();
+            set_all(&mut map, bits);
+            Repr::Map(ones, map)
+        }
+    }
+
+    /// Build from bits in any order, duplicates are ignored.
+    pub fn from_unsorted(bits: &[u16]) -> Repr {
+        if bits.len() <= Repr::VEC_SIZE {
+            let mut vec = bits.to_vec();
+            vec.sort_unstable();
+            vec.dedup();
+            return Repr::Vec(vec.len(), vec);
+        }
+
+        // Bucket into a bitarray, then read it back in order if it is sparse.
+        let mut map = Vec::new();
+        set_all(&mut map, bits);
+        Repr::from_words(&map)
+    }
+
+    /// Build from a bitarray laid out as in `Repr::Map`.
+    pub fn from_words(words: &[u64]) -> Repr {
+        let ones = words.iter().fold(0, |acc, w| acc + w.count_ones() as usize);
+        if ones <= Repr::VEC_SIZE {
+            let mut vec = Vec::with_capacity(ones);
+            for (i, &word) in words.iter().enumerate() {
+                let mut word = word;
+                while word != 0 {
+                    vec.push((i * Repr::BITS_SIZE) as u16 + word.trailing_zeros() as u16);
+                    word &= word - 1;
+                }
+            }
+            Repr::Vec(ones, vec)
+        } else {
+            let len = words.iter().rposition(|&w| w != 0).map_or(0, |i| i + 1);
+            Repr::Map(ones, words[..len].to_vec())
+        }
+    }
+
+    /// Insert ascending bits, duplicates are ignored.
+    /// Bits out of order are checked for, and sorted first.
+    pub fn extend_sorted(&mut self, bits: &[u16]) {
+        if !bits.is_sorted() {
+            let mut sorted = bits.to_vec();
+            sorted.sort_unstable();
+            return self.extend_sorted(&sorted);
+        }
+        let next = match self {
+            &mut Repr::Vec(_, ref vec) => {
+                let ones = merge(vec, bits).count();
+                if ones <= Repr::VEC_SIZE {
+                    let mut merged = Vec::with_capacity(ones);
+                    merged.extend(merge(vec, bits));
+                    Repr::Vec(ones, merged)
+                } else {
+                    let mut map = Vec::new();
+                    set_all(&mut map, vec);
+                    set_all(&mut map, bits);
+                    Repr::Map(ones, map)
+                }
+            }
+            &mut Repr::Map(ref mut ones, ref mut map) => {
+                *ones += set_all(map, bits);
+                return;
+            }
+        };
+        *self = next;
+    }
+}
+
+// Count of distinct bits in a sorted slice.
+fn distinct(bits: &[u16]) -> usize {
+    let dups = bits.windows(2).filter(|w| w[0] == w[1]).count();
+    bits.len() - dups
+}
+
+// Set each bit in `map`, growing it as needed, and return the count of newly set bits.
+fn set_all(map: &mut Vec<u64>, bits: &[u16]) -> usize {
+    let mut ones = 0;
+    for &bit in bits {
+        let key = bit as usize / Repr::BITS_SIZE;
+        let mask = 1 << (bit as usize % Repr::BITS_SIZE);
+        if key >= map.len() {
+            map.resize(key + 1, 0);
+        }
+        let word = &mut map[key];
+        ones += (*word & mask == 0) as usize;
+        *word |= mask;
+    }
+    ones
+}
+
+// Distinct bits of two ascending slices, in ascending order.
+struct Merge<'a> {
+    lhs: &'a [u16],
+    rhs: &'a [u16],
+    last: Option<u16>,
+}
+
+fn merge<'a>(lhs: &'a [u16], rhs: &'a [u16]) -> Merge<'a> {
+    Merge { lhs, rhs, last: None }
+}
+
+impl<'a> Iterator for Merge<'a> {
+    type Item = u16;
+    fn next(&mut self) -> Option<u16> {
+        loop {
+            let bit = match (self.lhs.first(), self.rhs.first()) {
+                (Some(&l), Some(&r)) if l <= r => {
+                    self.lhs = &self.lhs[1..];
+                    l
+                }
+                (_, Some(&r)) => {
+                    self.rhs = &self.rhs[1..];
+                    r
+                }
+                (Some(&l), None) => {
+                    self.lhs = &self.lhs[1..];
+                    l
+                }
+                (None, None) => return None,
+            };
+            if self.last != Some(bit) {
+                self.last = Some(bit);
+                return Some(bit);
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::io::{self, Read, Write};
+use alloc::vec::Vec;
+use super::{Bits, Repr};
+
+// Layout, all integers in little endian:
+//
+//   tag:  u8   0 for Vec, 1 for Map
+//   ones: u32
+//   len:  u32  count of u16 (Vec) or u64 (Map) that follow
+//   bits: [u16; len] or [u64; len]
+
+const TAG_VEC: u8 = 0;
+const TAG_MAP: u8 = 1;
+
+impl Repr {
+    /// Serialize this representation into `w`.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            &Repr::Vec(ones, ref bits) => {
+                w.write_all(&[TAG_VEC])?;
+                write_u32(w, ones as u32)?;
+                write_u32(w, bits.len() as u32)?;
+                for &bit in bits {
+                    w.write_all(&bit.to_le_bytes())?;
+                }
+            }
+            &Repr::Map(ones, ref bits) => {
+                w.write_all(&[TAG_MAP])?;
+                write_u32(w, ones as u32)?;
+                write_u32(w, bits.len() as u32)?;
+                for &word in bits {
+                    w.write_all(&word.to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Deserialize a representation written by `write_to`.
+    pub fn read_from<R: Read>(r: &mut R) -> io::Result<Repr> {
+        let mut tag = [0; 1];
+        r.read_exact(&mut tag)?;
+        let ones = read_u32(r)? as usize;
+        let len = read_u32(r)? as usize;
+        if ones > Repr::SIZE {
+            return Err(invalid_data("too many bits"));
+        }
+
+        match tag[0] {
+            TAG_VEC => {
+                if len != ones {
+                    return Err(invalid_data("length mismatch"));
+                }
+                let mut bits = Vec::with_capacity(len);
+                let mut buf = [0; 2];
+                for _ in 0..len {
+                    r.read_exact(&mut buf)?;
+                    let bit = u16::from_le_bytes(buf);
+                    if bits.last().is_some_and(|&last| last >= bit) {
+                        return Err(invalid_data("bits are not sorted"));
+                    }
+                    bits.push(bit);
+                }
+                Ok(Repr::Vec(ones, bits))
+            }
+            TAG_MAP => {
+                if len > Repr::MAP_SIZE {
+                    return Err(invalid_data("too many words"));
+                }
+                let mut bits = Vec::with_capacity(len);
+                let mut buf = [0; 8];
+                for _ in 0..len {
+                    r.read_exact(&mut buf)?;
+                    bits.push(u64::from_le_bytes(buf));
+                }
+                if bits.iter().fold(0, |acc, w| acc + w.ones()) != ones {
+                    return Err(invalid_data("ones mismatch"));
+                }
+                Ok(Repr::Map(ones, bits))
+            }
+            _ => Err(invalid_data("unknown tag")),
+        }
+    }
+}
+
+pub fn write_u32<W: Write>(w: &mut W, n: u32) -> io::Result<()> {
+    w.write_all(&n.to_le_bytes())
+}
+
+pub fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
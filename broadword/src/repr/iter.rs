@@ -1,11 +1,9 @@
-use std::iter::{Iterator, ExactSizeIterator};
-use std::slice::Iter as SliceIter;
-use std::marker::PhantomData;
+use core::iter::{Iterator, ExactSizeIterator};
+use core::slice::Iter as SliceIter;
+use core::marker::PhantomData;
 
 use super::{Bits, Repr};
 
-/// module document.
-
 // each 'ones' are count of non-zero bit; for size_hint
 pub enum Iter<'a> {
     Vec {
@@ -30,6 +28,14 @@ impl<'a> Iter<'a> {
         let ptr = SlicePtr::new_forward(bits);
         Iter::Map { ones, ptr }
     }
+    // Iterate bits, starting from `bit`.
+    pub fn map_at(bits: &'a [u64], ones: usize, bit: usize) -> Iter<'a> {
+        debug_assert!(ones <= Repr::SIZE);
+        let mut ptr = SlicePtr::new_forward(bits);
+        ptr.idx = bit / Repr::BITS_SIZE;
+        ptr.pos = bit % Repr::BITS_SIZE;
+        Iter::Map { ones, ptr }
+    }
 }
 
 impl<'a> Iterator for Iter<'a> {
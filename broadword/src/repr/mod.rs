@@ -1,5 +1,6 @@
-use std::{fmt, u16};
-use std::iter::{IntoIterator, FromIterator};
+use core::fmt;
+use alloc::vec::Vec;
+use core::iter::{IntoIterator, FromIterator};
 
 mod iter;
 pub use self::iter::Iter;
@@ -8,9 +9,17 @@ mod pair;
 mod bitand;
 mod bitor;
 mod bitxor;
+mod sub;
+mod threshold;
+#[cfg(feature = "simd")]
+pub mod simd;
 
 mod rank;
 mod select;
+mod bulk;
+
+#[cfg(feature = "std")]
+pub mod io;
 
 #[cfg(test)]
 mod repr_test;
@@ -38,12 +47,19 @@ impl Bits for Repr {
         }
     }
 }
+impl Default for Repr {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Repr {
     pub const BITS_SIZE: usize = <u64 as Bits>::SIZE;
 
     //pub const VEC_SIZE: usize = 1 << 12;
     //pub const VEC_SIZE: usize = 1 << 11;
-    const VEC_SIZE: usize = 1 << 10;
+    pub const VEC_SIZE: usize = 1 << 10;
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
     const MAP_SIZE: usize = Repr::SIZE / Repr::BITS_SIZE;
 
     #[allow(dead_code)]
@@ -87,13 +103,34 @@ impl Repr {
 }
 
 impl Repr {
-    fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         match self {
             &Repr::Vec(ones, ref bits) => Iter::vec(&bits[..], ones),
             &Repr::Map(ones, ref bits) => Iter::map(&bits[..], ones),
         }
     }
+
+    /// Iterate bits from the 'c+1'th non-zero bit.
+    pub fn iter_at(&self, c: usize) -> Iter<'_> {
+        if c >= self.ones() {
+            return Iter::vec(&[], 0);
+        }
+        match self {
+            &Repr::Vec(ones, ref bits) => Iter::vec(&bits[c..], ones - c),
+            &Repr::Map(ones, ref bits) => {
+                let bit = self.select1(c).unwrap_or(0);
+                Iter::map_at(&bits[..], ones - c, bit)
+            }
+        }
+    }
+}
+
+impl PartialEq for Repr {
+    fn eq(&self, that: &Repr) -> bool {
+        self.ones() == that.ones() && self.iter().eq(that.iter())
+    }
 }
+impl Eq for Repr {}
 
 impl fmt::Debug for Repr {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
@@ -125,7 +162,7 @@ impl Repr {
             &Repr::Vec(_, ref bits) => bits.binary_search(&bit).is_ok(),
             &Repr::Map(_, ref bits) => {
                 bitmask!(bit, key, mask);
-                bits.get(key).map_or(false, |map| *map & mask != 0)
+                bits.get(key).is_some_and(|map| *map & mask != 0)
             }
         }
     }
@@ -157,7 +194,7 @@ impl Repr {
                 }
                 bits.insert(key, mask);
                 *ones += 1;
-                return true;
+                true
             }
         }
     }
@@ -187,7 +224,7 @@ impl Repr {
                         return false;
                     };
                 }
-                return false;
+                false
             }
         }
     }
@@ -1,8 +1,8 @@
 #![allow(dead_code)]
 
-use std::iter::Peekable;
-use std::cmp::{self, Ordering};
-use std::marker::PhantomData;
+use core::iter::Peekable;
+use core::cmp::{self, Ordering};
+use core::marker::PhantomData;
 
 use self::Ordering::{Less, Equal, Greater};
 use super::Iter;
@@ -63,11 +63,12 @@ impl<'a> Iterator for Pair<'a, Intersection> {
     type Item = u16;
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            match {
-                      let x = self.lhs.peek();
-                      let y = self.rhs.peek();
-                      x.and_then(|x1| y.map(|y1| x1.cmp(&y1)))
-                  } {
+            let ord = {
+                let x = self.lhs.peek();
+                let y = self.rhs.peek();
+                x.and_then(|x1| y.map(|y1| x1.cmp(y1)))
+            };
+            match ord {
                 None => return None,
                 Some(Less) => {
                     self.lhs.next();
@@ -90,15 +91,13 @@ impl<'a> Iterator for Pair<'a, Intersection> {
 impl<'a> Iterator for Pair<'a, Union> {
     type Item = u16;
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match comparing(self.lhs.peek(), self.rhs.peek(), Greater, Less) {
-                Less => return self.lhs.next(),
-                Equal => {
-                    self.rhs.next();
-                    return self.lhs.next();
-                }
-                Greater => return self.rhs.next(),
+        match comparing(self.lhs.peek(), self.rhs.peek(), Greater, Less) {
+            Less => self.lhs.next(),
+            Equal => {
+                self.rhs.next();
+                self.lhs.next()
             }
+            Greater => self.rhs.next(),
         }
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
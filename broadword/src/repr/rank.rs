@@ -20,7 +20,7 @@ impl Rank for Repr {
                 bits.get(q).map_or(0, |w| w.rank1(r))
             }
         };
-        return rank;
+        rank
     }
     fn rank0(&self, i: usize) -> usize {
         i - self.rank1(i)
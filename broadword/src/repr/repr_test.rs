@@ -4,8 +4,6 @@
 
 extern crate rand;
 use self::rand::Rng;
-extern crate test;
-use self::test::Bencher;
 
 use super::*;
 
@@ -68,11 +66,11 @@ impl RankSelect {
     }
 }
 
-static LENGTHS: &'static [usize] = &[0,
-                                     Repr::VEC_SIZE,
-                                     Repr::VEC_SIZE * 2,
-                                     Repr::SIZE / 2,
-                                     Repr::SIZE];
+static LENGTHS: &[usize] = &[0,
+                             Repr::VEC_SIZE,
+                             Repr::VEC_SIZE * 2,
+                             Repr::SIZE / 2,
+                             Repr::SIZE];
 
 #[test]
 fn repr_rank_select_properties() {
@@ -123,7 +121,7 @@ fn repr_iter_next() {
 struct BitsOps<'a> {
     lhs: &'a Repr,
     rhs: &'a Repr,
-    ops: &'a Fn(&Repr, &Repr) -> Repr,
+    ops: &'a dyn Fn(&Repr, &Repr) -> Repr,
 }
 
 impl<'a> BitsOps<'a> {
@@ -156,6 +154,9 @@ macro_rules! init_bitops {
     ( $test: ident, $lhs: ident ^ $rhs: ident ) => {
         init_bitops!( $test, $lhs, $rhs, &(|x, y| x ^ y) );
     };
+    ( $test: ident, $lhs: ident - $rhs: ident ) => {
+        init_bitops!( $test, $lhs, $rhs, &(|x, y| x - y) );
+    };
 
     ( $test: ident, $lhs: ident, $rhs: ident, $ops: expr ) => {
         let $test = BitsOps { lhs: $lhs, rhs: $rhs, ops: $ops };
@@ -181,6 +182,12 @@ macro_rules! bitops {
         init_repr!($that; $rhs, rng);
         init_bitops!($test, $lhs ^ $rhs);
     };
+    ( $this: ident - $that: ident; $lhs: ident, $rhs: ident, $test: ident ) => {
+        let mut rng = rand::thread_rng();
+        init_repr!($this; $lhs, rng);
+        init_repr!($that; $rhs, rng);
+        init_bitops!($test, $lhs - $rhs);
+    };
 }
 
 macro_rules! bitops_test {
@@ -230,6 +237,22 @@ macro_rules! bitops_test {
             assert!(i == j, "i:{:?} j:{:?}", i, j);
         }
     };
+    ( $this: ident - $that: ident ) => {
+        bitops!($this - $that; lhs, rhs, test);
+        let sub = test.run();
+        for bit in &sub {
+            assert!(lhs.contains(bit) && !rhs.contains(bit), "{:?}", bit);
+        }
+        let pair = {
+            let x = lhs.iter();
+            let y = rhs.iter();
+            pair::difference(x, y)
+        };
+        assert_eq!(sub.ones(), pair.count());
+        let mut assign = lhs.clone();
+        assign -= rhs;
+        assert_eq!(assign, sub);
+    };
 }
 
 #[test]
@@ -248,6 +271,166 @@ fn repr_bitops() {
     bitops_test!(VEC ^ MAP);
     bitops_test!(MAP ^ VEC);
     bitops_test!(MAP ^ MAP);
+
+    bitops_test!(VEC - VEC);
+    bitops_test!(VEC - MAP);
+    bitops_test!(MAP - VEC);
+    bitops_test!(MAP - MAP);
+}
+
+#[test]
+fn repr_bitops_map_len() {
+    // Map words are allocated lazily, so two maps may differ in length.
+    let short = Repr::Map(2, vec![1, 1]);
+    let long = Repr::Map(4, vec![1, 1, 0, 1 << 63 | 1]);
+    for &(lhs, rhs) in &[(&short, &long), (&long, &short)] {
+        let and = lhs & rhs;
+        let or = lhs | rhs;
+        let xor = lhs ^ rhs;
+        let sub = lhs - rhs;
+        assert_eq!(sub.ones(), sub.iter().count());
+        assert_eq!(and.ones(), and.iter().count());
+        assert_eq!(or.ones(), or.iter().count());
+        assert_eq!(xor.ones(), xor.iter().count());
+        assert_eq!(and.iter().collect::<Vec<u16>>(), vec![0, 64]);
+        assert_eq!(or.iter().collect::<Vec<u16>>(), vec![0, 64, 192, 255]);
+        assert_eq!(xor.iter().collect::<Vec<u16>>(), vec![192, 255]);
+    }
+}
+
+#[test]
+fn repr_threshold() {
+    let mut rng = rand::thread_rng();
+    // Sparse only, dense only, and both, with a short map.
+    let sizes: &[&[usize]] = &[&[0, 10, 500, 1000, 50],
+                               &[5000, 20000, 40000],
+                               &[10, 3000, 60000, 0, 700, 30000, 2]];
+    for sizes in sizes {
+        let mut reprs = sizes.iter().map(|&size| ReprTest::with_capacity(size, &mut rng))
+            .collect::<Vec<Repr>>();
+        reprs.push(Repr::Map(2, vec![1, 1]));
+        let mut counts = vec![0; Repr::SIZE];
+        for repr in &reprs {
+            for bit in repr {
+                counts[bit as usize] += 1;
+            }
+        }
+        let refs = reprs.iter().collect::<Vec<&Repr>>();
+        for k in 1..refs.len() + 2 {
+            let got = Repr::threshold(&refs, k);
+            let want = (0..Repr::SIZE).filter(|&i| counts[i] >= k).map(|i| i as u16);
+            assert!(got.iter().eq(want), "k = {}", k);
+            assert_eq!(got.ones(), got.iter().count());
+        }
+    }
+    assert_eq!(Repr::threshold(&[], 1).ones(), 0);
+}
+
+#[test]
+fn repr_bulk_load() {
+    let mut rng = rand::thread_rng();
+    let sizes = [0, 1, 100, Repr::VEC_SIZE, Repr::VEC_SIZE + 1, Repr::VEC_SIZE * 4, Repr::SIZE];
+    for &size in &sizes {
+        for &range in &[size / 2 + 1, Repr::SIZE] {
+            let mut bits = (0..size).map(|_| rng.gen_range(0, range) as u16).collect::<Vec<u16>>();
+            let want = bits.iter().collect::<Repr>();
+            let fitted = |repr: &Repr| match repr {
+                &Repr::Vec(ones, _) => ones <= Repr::VEC_SIZE,
+                &Repr::Map(ones, _) => ones > Repr::VEC_SIZE,
+            };
+
+            let unsorted = Repr::from_unsorted(&bits);
+            assert_eq!(unsorted, want);
+            assert!(fitted(&unsorted));
+
+            // Bits out of order are found, not taken as sorted.
+            assert_eq!(Repr::from_sorted_slice(&bits), want);
+            let mut extended = Repr::new();
+            extended.extend_sorted(&bits);
+            assert_eq!(extended, want);
+
+            bits.sort();
+            let sorted = Repr::from_sorted_slice(&bits);
+            assert_eq!(sorted, want);
+            assert!(fitted(&sorted));
+
+            let (lo, hi) = bits.split_at(size / 3);
+            for mut repr in [Repr::from_sorted_slice(lo), lo.iter().collect::<Repr>()] {
+                repr.extend_sorted(hi);
+                repr.extend_sorted(lo);
+                assert_eq!(repr, want);
+                assert_eq!(repr.ones(), repr.iter().count());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "simd")]
+#[test]
+fn repr_simd_vec_ops() {
+    let mut rng = rand::thread_rng();
+    let gen = |rng: &mut rand::ThreadRng, size: usize, range: u32| {
+        let bits = (0..size).map(|_| rng.gen_range(0, range) as u16).collect::<Vec<u16>>();
+        let mut bits = bits;
+        bits.sort();
+        bits.dedup();
+        bits
+    };
+    let sizes = [0, 1, 7, 8, 9, 15, 16, 17, 100, Repr::VEC_SIZE];
+    for _ in 0..20 {
+        for &x in &sizes {
+            for &y in &sizes {
+                // Narrow ranges make many common values, wide ones make few.
+                let range = *rng.choose(&[16u32, 64, 1 << 11, 1 << 16]).unwrap();
+                let a = gen(&mut rng, x, range);
+                let b = gen(&mut rng, y, range);
+                let (va, vb) = (Repr::Vec(a.len(), a.clone()), Repr::Vec(b.len(), b.clone()));
+                let and = pair::intersection(va.iter(), vb.iter()).collect::<Vec<u16>>();
+                let or = pair::union(va.iter(), vb.iter()).collect::<Vec<u16>>();
+
+                let mut out = Vec::new();
+                simd::intersect(&a, &b, &mut out);
+                assert_eq!(out, and, "{:?} {:?}", a, b);
+                out.clear();
+                simd::union(&a, &b, &mut out);
+                assert_eq!(out, or, "{:?} {:?}", a, b);
+
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                {
+                    if is_x86_feature_detected!("sse4.2") {
+                        out.clear();
+                        unsafe { simd::x86::intersect(&a, &b, &mut out) };
+                        assert_eq!(out, and, "{:?} {:?}", a, b);
+                        out.clear();
+                        unsafe { simd::x86::union(&a, &b, &mut out) };
+                        assert_eq!(out, or, "{:?} {:?}", a, b);
+                    }
+                }
+
+                // A value equal to the last one in `out` is not appended again.
+                if let Some(&head) = or.first() {
+                    out = vec![head];
+                    simd::union_scalar(&a, &b, &mut out);
+                    assert_eq!(out, or, "{:?} {:?}", a, b);
+                    out = vec![head];
+                    simd::union(&a, &b, &mut out);
+                    assert_eq!(out, or, "{:?} {:?}", a, b);
+                    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                    {
+                        if is_x86_feature_detected!("sse4.2") {
+                            out = vec![head];
+                            unsafe { simd::x86::union(&a, &b, &mut out) };
+                            assert_eq!(out, or, "{:?} {:?}", a, b);
+                        }
+                    }
+                }
+
+                assert!((&va & &vb).iter().eq(and.iter().cloned()));
+                assert!((&va | &vb).iter().eq(or.iter().cloned()));
+                assert_eq!((&va | &vb).ones(), or.len());
+            }
+        }
+    }
 }
 
 #[test]
@@ -257,7 +440,7 @@ fn repr_insert_remove() {
     let mut b = Repr::none();
     let mut i = 0u16;
     while i < Repr::VEC_SIZE as u16 {
-        assert!(b.insert(i), format!("insert({:?}) failed", i));
+        assert!(b.insert(i), "insert({:?}) failed", i);
         assert!(b.contains(i));
         i += 1;
     }
@@ -267,7 +450,7 @@ fn repr_insert_remove() {
         assert!(b.contains(i), "insert ok, but not contains");
         i += 1;
     }
-    assert!(b.insert(i), format!("insert({:?}) failed", i));
+    assert!(b.insert(i), "insert({:?}) failed", i);
     assert!(b.contains(i));
 
     b.optimize();
@@ -276,11 +459,11 @@ fn repr_insert_remove() {
     assert_eq!(b.ones(), Repr::VEC_SIZE + 1);
 
     while i > 0 {
-        assert!(b.remove(i), format!("remove({:?}) failed", i));
+        assert!(b.remove(i), "remove({:?}) failed", i);
         assert!(!b.contains(i));
         i -= 1;
     }
-    assert!(b.remove(i), format!("remove({:?}) failed", i));
+    assert!(b.remove(i), "remove({:?}) failed", i);
     assert_eq!(i, 0);
     assert_eq!(b.ones(), 0);
 
@@ -288,65 +471,71 @@ fn repr_insert_remove() {
     assert_eq!(0, b.ones());
 }
 
-macro_rules! run_bench_bitops {
-    ( $this: ident & $that: ident; $bench: expr ) => {
-        bitops!($this & $that; lhs, rhs, test); $bench.iter(|| test.run());
-    };
-    ( $this: ident | $that: ident; $bench: expr ) => {
-        bitops!($this | $that; lhs, rhs, test); $bench.iter(|| test.run());
-    };
-    ( $this: ident ^ $that: ident; $bench: expr ) => {
-        bitops!($this | $that; lhs, rhs, test); $bench.iter(|| test.run());
-    };
-}
+#[cfg(feature = "nightly")]
+mod benches {
+    use test::Bencher;
+    use super::*;
 
-#[bench]
-fn VEC_and_VEC(bench: &mut Bencher) {
-    run_bench_bitops!(VEC & VEC; bench);
-}
-#[bench]
-fn VEC_and_MAP(bench: &mut Bencher) {
-    run_bench_bitops!(VEC & MAP; bench);
-}
-#[bench]
-fn MAP_and_VEC(bench: &mut Bencher) {
-    run_bench_bitops!(MAP & VEC; bench);
-}
-#[bench]
-fn MAP_and_MAP(bench: &mut Bencher) {
-    run_bench_bitops!(MAP & MAP; bench);
-}
+    macro_rules! run_bench_bitops {
+        ( $this: ident & $that: ident; $bench: expr ) => {
+            bitops!($this & $that; lhs, rhs, test); $bench.iter(|| test.run());
+        };
+        ( $this: ident | $that: ident; $bench: expr ) => {
+            bitops!($this | $that; lhs, rhs, test); $bench.iter(|| test.run());
+        };
+        ( $this: ident ^ $that: ident; $bench: expr ) => {
+            bitops!($this | $that; lhs, rhs, test); $bench.iter(|| test.run());
+        };
+    }
 
-#[bench]
-fn VEC_or_VEC(bench: &mut Bencher) {
-    run_bench_bitops!(VEC | VEC; bench);
-}
-#[bench]
-fn VEC_or_MAP(bench: &mut Bencher) {
-    run_bench_bitops!(VEC | MAP; bench);
-}
-#[bench]
-fn MAP_or_VEC(bench: &mut Bencher) {
-    run_bench_bitops!(MAP | VEC; bench);
-}
-#[bench]
-fn MAP_or_MAP(bench: &mut Bencher) {
-    run_bench_bitops!(MAP | MAP; bench);
-}
+    #[bench]
+    fn VEC_and_VEC(bench: &mut Bencher) {
+        run_bench_bitops!(VEC & VEC; bench);
+    }
+    #[bench]
+    fn VEC_and_MAP(bench: &mut Bencher) {
+        run_bench_bitops!(VEC & MAP; bench);
+    }
+    #[bench]
+    fn MAP_and_VEC(bench: &mut Bencher) {
+        run_bench_bitops!(MAP & VEC; bench);
+    }
+    #[bench]
+    fn MAP_and_MAP(bench: &mut Bencher) {
+        run_bench_bitops!(MAP & MAP; bench);
+    }
 
-#[bench]
-fn VEC_xor_VEC(bench: &mut Bencher) {
-    run_bench_bitops!(VEC ^ VEC; bench);
-}
-#[bench]
-fn VEC_xor_MAP(bench: &mut Bencher) {
-    run_bench_bitops!(VEC ^ MAP; bench);
-}
-#[bench]
-fn MAP_xor_VEC(bench: &mut Bencher) {
-    run_bench_bitops!(MAP ^ VEC; bench);
-}
-#[bench]
-fn MAP_xor_MAP(bench: &mut Bencher) {
-    run_bench_bitops!(MAP ^ MAP; bench);
+    #[bench]
+    fn VEC_or_VEC(bench: &mut Bencher) {
+        run_bench_bitops!(VEC | VEC; bench);
+    }
+    #[bench]
+    fn VEC_or_MAP(bench: &mut Bencher) {
+        run_bench_bitops!(VEC | MAP; bench);
+    }
+    #[bench]
+    fn MAP_or_VEC(bench: &mut Bencher) {
+        run_bench_bitops!(MAP | VEC; bench);
+    }
+    #[bench]
+    fn MAP_or_MAP(bench: &mut Bencher) {
+        run_bench_bitops!(MAP | MAP; bench);
+    }
+
+    #[bench]
+    fn VEC_xor_VEC(bench: &mut Bencher) {
+        run_bench_bitops!(VEC ^ VEC; bench);
+    }
+    #[bench]
+    fn VEC_xor_MAP(bench: &mut Bencher) {
+        run_bench_bitops!(VEC ^ MAP; bench);
+    }
+    #[bench]
+    fn MAP_xor_VEC(bench: &mut Bencher) {
+        run_bench_bitops!(MAP ^ VEC; bench);
+    }
+    #[bench]
+    fn MAP_xor_MAP(bench: &mut Bencher) {
+        run_bench_bitops!(MAP ^ MAP; bench);
+    }
 }
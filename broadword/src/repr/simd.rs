@@ -0,0 +1,255 @@
+//! Intersection and union of sorted `u16` arrays, vectorized with SSE4.2 where available.
+//!
+//! Schlegel, Willhalm and Lehner, "Fast Sorted-Set Intersection using SIMD Instructions";
+//! Lemire, Ssi-Yan-Kai and Kaser, "Consistently faster and smaller compressed bitmaps with Roaring".
+
+use alloc::vec::Vec;
+use super::Repr;
+
+fn bits(repr: &Repr) -> &[u16] {
+    match repr {
+        &Repr::Vec(_, ref bits) => bits,
+        &Repr::Map(..) => unreachable!("expected Repr::Vec"),
+    }
+}
+
+// Intersection of two `Repr::Vec`.
+pub fn vec_and(vec0: &Repr, vec1: &Repr) -> Repr {
+    let (a, b) = (bits(vec0), bits(vec1));
+    let mut out = Vec::new();
+    intersect(a, b, &mut out);
+    Repr::Vec(out.len(), out)
+}
+
+// Union of two `Repr::Vec`.
+pub fn vec_or(vec0: &Repr, vec1: &Repr) -> Repr {
+    let (a, b) = (bits(vec0), bits(vec1));
+    let mut out = Vec::new();
+    union(a, b, &mut out);
+    if out.len() <= Repr::VEC_SIZE {
+        Repr::Vec(out.len(), out)
+    } else {
+        Repr::from_sorted_slice(&out)
+    }
+}
+
+/// Append the intersection of strictly ascending `a` and `b` to `out`.
+pub fn intersect(a: &[u16], b: &[u16], out: &mut Vec<u16>) {
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+    {
+        if is_x86_feature_detected!("sse4.2") {
+            return unsafe { x86::intersect(a, b, out) };
+        }
+    }
+    // Without `std`, SSE4.2 is used only if the target enables it.
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"),
+              not(feature = "std"),
+              target_feature = "sse4.2"))]
+    {
+        return unsafe { x86::intersect(a, b, out) };
+    }
+    #[allow(unreachable_code)]
+    intersect_scalar(a, b, out)
+}
+
+/// Append the union of strictly ascending `a` and `b` to `out`,
+/// skipping values equal to the last one in `out`.
+pub fn union(a: &[u16], b: &[u16], out: &mut Vec<u16>) {
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "std"))]
+    {
+        if is_x86_feature_detected!("sse4.2") {
+            return unsafe { x86::union(a, b, out) };
+        }
+    }
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"),
+              not(feature = "std"),
+              target_feature = "sse4.2"))]
+    {
+        return unsafe { x86::union(a, b, out) };
+    }
+    #[allow(unreachable_code)]
+    union_scalar(a, b, out)
+}
+
+pub fn intersect_scalar(a: &[u16], b: &[u16], out: &mut Vec<u16>) {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] < b[j] {
+            i += 1;
+        } else if a[i] > b[j] {
+            j += 1;
+        } else {
+            out.push(a[i]);
+            i += 1;
+            j += 1;
+        }
+    }
+}
+
+// Also drops values equal to the last one in `out`, so that vectorized
+// merges can hand their leftovers over to it.
+pub fn union_scalar(a: &[u16], b: &[u16], out: &mut Vec<u16>) {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let x = if j == b.len() || (i < a.len() && a[i] <= b[j]) {
+            i += 1;
+            a[i - 1]
+        } else {
+            j += 1;
+            b[j - 1]
+        };
+        if out.last() != Some(&x) {
+            out.push(x);
+        }
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub mod x86 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+    use alloc::vec::Vec;
+
+    const LANES: usize = 8;
+
+    // `SHUFFLE[m]` moves the lanes set in `m` to the front.
+    static SHUFFLE: [[u8; 16]; 256] = shuffle();
+
+    const fn shuffle() -> [[u8; 16]; 256] {
+        let mut table = [[0xFF; 16]; 256];
+        let mut m = 0;
+        while m < 256 {
+            let (mut k, mut n) = (0, 0);
+            while k < LANES {
+                if m & 1 << k != 0 {
+                    table[m][2 * n] = 2 * k as u8;
+                    table[m][2 * n + 1] = 2 * k as u8 + 1;
+                    n += 1;
+                }
+                k += 1;
+            }
+            m += 1;
+        }
+        table
+    }
+
+    #[inline]
+    unsafe fn load(xs: &[u16], i: usize) -> __m128i {
+        debug_assert!(i + LANES <= xs.len());
+        _mm_loadu_si128(xs.as_ptr().add(i) as *const __m128i)
+    }
+
+    // Write the lanes set in `mask` to the end of `out`, which has room for a whole vector.
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn store(v: __m128i, mask: usize, out: &mut Vec<u16>) {
+        debug_assert!(out.capacity() - out.len() >= LANES);
+        let shuffle = _mm_loadu_si128(SHUFFLE[mask].as_ptr() as *const __m128i);
+        let shuffled = _mm_shuffle_epi8(v, shuffle);
+        let len = out.len();
+        _mm_storeu_si128(out.as_mut_ptr().add(len) as *mut __m128i, shuffled);
+        out.set_len(len + mask.count_ones() as usize);
+    }
+
+    /// REQUIRES: SSE4.2
+    #[target_feature(enable = "sse4.2")]
+    pub unsafe fn intersect(a: &[u16], b: &[u16], out: &mut Vec<u16>) {
+        const MODE: i32 = _SIDD_UWORD_OPS | _SIDD_CMP_EQUAL_ANY | _SIDD_BIT_MASK;
+        let (la, lb) = (a.len() / LANES * LANES, b.len() / LANES * LANES);
+        out.reserve(a.len().min(b.len()) + LANES);
+        let (mut i, mut j) = (0, 0);
+        if i < la && j < lb {
+            let mut va = load(a, i);
+            let mut vb = load(b, j);
+            loop {
+                // Lanes of `va` equal to any lane of `vb`.
+                let found = _mm_cmpestrm::<MODE>(vb, LANES as i32, va, LANES as i32);
+                store(va, _mm_cvtsi128_si32(found) as usize & 0xFF, out);
+                let (amax, bmax) = (a[i + LANES - 1], b[j + LANES - 1]);
+                if amax <= bmax {
+                    i += LANES;
+                    if i == la {
+                        break;
+                    }
+                    va = load(a, i);
+                }
+                if bmax <= amax {
+                    j += LANES;
+                    if j == lb {
+                        break;
+                    }
+                    vb = load(b, j);
+                }
+            }
+        }
+        super::intersect_scalar(&a[i..], &b[j..], out);
+    }
+
+    // Merge two ascending vectors into the lower and the upper 8 values.
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn merge(a: __m128i, b: __m128i) -> (__m128i, __m128i) {
+        let mut min = _mm_min_epu16(a, b);
+        let mut max = _mm_max_epu16(a, b);
+        for _ in 0..LANES - 1 {
+            let rotated = _mm_alignr_epi8::<2>(min, min);
+            min = _mm_min_epu16(rotated, max);
+            max = _mm_max_epu16(rotated, max);
+        }
+        (_mm_alignr_epi8::<2>(min, min), max)
+    }
+
+    // Store lanes of `v` which differ from the previous lane,
+    // the lane before the first one is the last lane of `last`.
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn store_unique(last: __m128i, v: __m128i, out: &mut Vec<u16>) {
+        let prev = _mm_alignr_epi8::<14>(v, last);
+        let dups = _mm_packs_epi16(_mm_cmpeq_epi16(prev, v), _mm_setzero_si128());
+        store(v, !_mm_movemask_epi8(dups) as usize & 0xFF, out);
+    }
+
+    /// REQUIRES: SSE4.2
+    #[target_feature(enable = "sse4.2")]
+    pub unsafe fn union(a: &[u16], b: &[u16], out: &mut Vec<u16>) {
+        if a.len() < LANES || b.len() < LANES {
+            return super::union_scalar(a, b, out);
+        }
+        let (la, lb) = (a.len() / LANES * LANES, b.len() / LANES * LANES);
+        out.reserve(a.len() + b.len() + LANES);
+        let start = out.len();
+
+        let (mut min, mut max) = merge(load(a, 0), load(b, 0));
+        let (mut i, mut j) = (LANES, LANES);
+        // Skip a value equal to the last one in `out`. Without one, the smallest value
+        // is at most `u16::MAX - 7`, so it never equals the sentinel.
+        let mut last = _mm_set1_epi16(out.last().map_or(-1, |&x| x as i16));
+        store_unique(last, min, out);
+        last = min;
+        // Load from the array with the smaller head, so the lower half is final.
+        while i < la && j < lb {
+            let v = if a[i] <= b[j] {
+                i += LANES;
+                load(a, i - LANES)
+            } else {
+                j += LANES;
+                load(b, j - LANES)
+            };
+            let merged = merge(v, max);
+            min = merged.0;
+            max = merged.1;
+            store_unique(last, min, out);
+            last = min;
+        }
+        debug_assert!(out.len() > start);
+
+        let mut rest = [0u16; LANES];
+        _mm_storeu_si128(rest.as_mut_ptr() as *mut __m128i, max);
+        let mut tmp = Vec::with_capacity(LANES + a.len() - i);
+        super::union_scalar(&rest, &a[i..], &mut tmp);
+        super::union_scalar(&tmp, &b[j..], out);
+    }
+}
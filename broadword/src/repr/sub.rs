@@ -0,0 +1,76 @@
+use core::ops;
+use super::{pair, Bits, Repr};
+
+macro_rules! difference {
+    ( $iter: ident, $vec0: expr, $vec1: expr ) => {
+        let $iter = {
+            let i0 = $vec0.iter();
+            let i1 = $vec1.iter();
+            pair::difference(i0, i1)
+        };
+    };
+}
+
+impl Repr {
+    fn difference_with(&mut self, that: &Repr) {
+        match (self, that) {
+            (vec0 @ &mut Repr::Vec(..), vec1 @ &Repr::Vec(..)) => {
+                let repr = vec0.clone();
+                difference!(iter, repr, vec1);
+                *vec0 = iter.collect::<Repr>();
+            }
+
+            (&mut Repr::Vec(ref mut ones, ref mut bits0), map_repr @ &Repr::Map(..)) => {
+                *ones = 0;
+                for i in 0..bits0.len() {
+                    if !map_repr.contains(bits0[i]) {
+                        bits0[*ones] = bits0[i];
+                        *ones += 1;
+                    }
+                }
+                bits0.truncate(*ones);
+            }
+
+            (ref mut repr @ &mut Repr::Map(..), &Repr::Vec(_, ref bits)) => {
+                for &b in bits {
+                    repr.remove(b);
+                }
+            }
+
+            (&mut Repr::Map(ref mut ones, ref mut bits0), &Repr::Map(_, ref bits1)) => {
+                *ones = 0;
+                for (x, y) in bits0.iter_mut().zip(bits1.iter()) {
+                    let p = *x & !*y;
+                    *ones += p.ones();
+                    *x = p;
+                }
+                // Words beyond `bits1` are kept as is.
+                if bits0.len() > bits1.len() {
+                    *ones += bits0[bits1.len()..].iter().fold(0, |acc, w| acc + w.ones());
+                }
+            }
+        }
+    }
+}
+
+impl<'a, 'b> ops::Sub<&'b Repr> for &'a Repr {
+    type Output = Repr;
+    fn sub(self, that: &Repr) -> Self::Output {
+        match (self, that) {
+            (vec0 @ &Repr::Vec(..), vec1 @ &Repr::Vec(..)) => {
+                difference!(iter, vec0, vec1);
+                iter.collect::<Repr>()
+            }
+            (this, that) => {
+                let mut clone = this.clone();
+                clone.difference_with(that);
+                clone
+            }
+        }
+    }
+}
+impl<'a> ops::SubAssign<&'a Repr> for Repr {
+    fn sub_assign(&mut self, that: &Repr) {
+        self.difference_with(that);
+    }
+}
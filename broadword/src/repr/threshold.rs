@@ -0,0 +1,115 @@
+use core::cmp::Reverse;
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+
+use super::{Bits, Repr};
+
+// Count of words of a `Repr::Map`.
+const WORDS: usize = Repr::SIZE / Repr::BITS_SIZE;
+
+impl Repr {
+    /// Bits set in at least `k` of `reprs`.
+    pub fn threshold(reprs: &[&Repr], k: usize) -> Repr {
+        assert!(k > 0, "threshold must be positive");
+        if k > reprs.len() {
+            return Repr::new();
+        }
+        let sparse = reprs.iter().all(|repr| matches!(repr, &&Repr::Vec(..)));
+        let mut repr = if sparse { merge(reprs, k) } else { count(reprs, k) };
+        repr.optimize();
+        repr
+    }
+}
+
+// Merge sorted bits of `Repr::Vec`s, counting equal ones.
+fn merge(reprs: &[&Repr], k: usize) -> Repr {
+    let mut iters = reprs.iter().map(|repr| repr.iter()).collect::<Vec<_>>();
+    let mut heap = BinaryHeap::with_capacity(iters.len());
+    for (i, iter) in iters.iter_mut().enumerate() {
+        if let Some(bit) = iter.next() {
+            heap.push(Reverse((bit, i)));
+        }
+    }
+    let mut bits = Vec::new();
+    while let Some(Reverse((bit, i))) = heap.pop() {
+        let mut count = 1;
+        if let Some(next) = iters[i].next() {
+            heap.push(Reverse((next, i)));
+        }
+        while let Some(&Reverse((same, j))) = heap.peek() {
+            if same != bit {
+                break;
+            }
+            heap.pop();
+            count += 1;
+            if let Some(next) = iters[j].next() {
+                heap.push(Reverse((next, j)));
+            }
+        }
+        if count >= k {
+            bits.push(bit);
+        }
+    }
+    Repr::Vec(bits.len(), bits)
+}
+
+// Count each bit of the words of `reprs` in bit-sliced counters,
+// where `counters[j]` holds bit `j` of the count of each of 64 bits.
+fn count(reprs: &[&Repr], k: usize) -> Repr {
+    let planes = (usize::BITS - reprs.len().leading_zeros()) as usize;
+    let mut counters = vec![0u64; planes];
+    let mut cursors = vec![0; reprs.len()];
+    let mut words = Vec::with_capacity(WORDS);
+    let mut ones = 0;
+    for w in 0..WORDS {
+        counters.iter_mut().for_each(|c| *c = 0);
+        for (repr, cursor) in reprs.iter().zip(&mut cursors) {
+            let mut carry = word(repr, w, cursor);
+            // Add to each counter of a set bit, as a ripple-carry adder.
+            for counter in counters.iter_mut() {
+                if carry == 0 {
+                    break;
+                }
+                let next = *counter & carry;
+                *counter ^= carry;
+                carry = next;
+            }
+        }
+        let word = at_least(&counters, k);
+        ones += word.ones();
+        words.push(word);
+    }
+    Repr::Map(ones, words)
+}
+
+// Word `w` of `repr`, where `cursor` is the position of the next bit of a `Repr::Vec`.
+fn word(repr: &Repr, w: usize, cursor: &mut usize) -> u64 {
+    match repr {
+        &Repr::Map(_, ref bits) => bits.get(w).cloned().unwrap_or(0),
+        &Repr::Vec(_, ref bits) => {
+            let mut word = 0;
+            while let Some(&bit) = bits.get(*cursor) {
+                if bit as usize / Repr::BITS_SIZE != w {
+                    break;
+                }
+                word |= 1 << (bit as usize % Repr::BITS_SIZE);
+                *cursor += 1;
+            }
+            word
+        }
+    }
+}
+
+// Bits whose count is at least `k`, comparing from the most significant counter.
+fn at_least(counters: &[u64], k: usize) -> u64 {
+    let (mut greater, mut equal) = (0, !0);
+    for (j, &counter) in counters.iter().enumerate().rev() {
+        if k >> j & 1 != 0 {
+            equal &= counter;
+        } else {
+            greater |= equal & counter;
+            equal &= !counter;
+        }
+    }
+    greater | equal
+}
@@ -0,0 +1,291 @@
+//! Range minimum queries in 2n + o(n) bits, without the values.
+//!
+//! Fischer and Heun, "Space-efficient preprocessing schemes for range minimum queries
+//! on static arrays", SIAM J. Comput. 2011;
+//! Ferrada and Navarro, "Improved range minimum queries", DCC 2016;
+//! Navarro and Sadakane, "Fully functional static and dynamic succinct trees", 2014.
+
+use core::ops::Range;
+use alloc::vec::Vec;
+
+use super::bitvec::BitVec;
+use super::{Rank, Select1};
+
+#[cfg(test)]
+mod rmq_test;
+
+// Excess minima are summarized per block, and per superblock of blocks.
+const BLOCK_BITS: usize = 512;
+const SUPER_BLOCKS: usize = 64;
+const SUPER_BITS: usize = SUPER_BLOCKS * BLOCK_BITS;
+
+// `MIN[b]` and `EXCESS[b]` are the least and the last excess after each bit of byte `b`.
+static MIN: [i8; 256] = excess_table().0;
+static EXCESS: [i8; 256] = excess_table().1;
+
+const fn excess_table() -> ([i8; 256], [i8; 256]) {
+    let mut min = [0; 256];
+    let mut excess = [0; 256];
+    let mut b = 0;
+    while b < 256 {
+        let (mut e, mut m) = (0, 8);
+        let mut k = 0;
+        while k < 8 {
+            e += if b >> k & 1 != 0 { 1 } else { -1 };
+            if e < m {
+                m = e;
+            }
+            k += 1;
+        }
+        min[b] = m;
+        excess[b] = e;
+        b += 1;
+    }
+    (min, excess)
+}
+
+/// Index for the position of the minimum of any range of a static array.
+///
+/// Each value's parent is the closest value to its left not greater than it.
+/// The tree is stored as balanced parentheses in preorder, which is the order of the array,
+/// so that the leftmost minimum of a range is its rightmost node of the least depth.
+/// The least excess of parentheses is kept for each block of 512 bits relative to its superblock,
+/// and for each superblock of 64 blocks in a sparse table over ranges of superblocks,
+/// so that a query reads a bounded count of bytes, blocks and table entries,
+/// besides finding the parentheses of the ends of the range with `select`.
+#[derive(Clone, Debug)]
+pub struct Rmq {
+    len: usize,
+    bp: BitVec,
+    // Least excess in each block, relative to the excess before its superblock.
+    blocks: Vec<i16>,
+    // Least excess in each superblock.
+    supers: Vec<isize>,
+    // `table[k - 1][s]` is the rightmost superblock of the least excess in `s..s + 2^k`.
+    table: Vec<Vec<u32>>,
+}
+
+impl Rmq {
+    pub fn new<T: Ord>(values: &[T]) -> Rmq {
+        // An open parenthesis for a virtual root, then one for each value,
+        // closing values greater than it first.
+        let mut bits = Vec::with_capacity(2 * values.len() + 2);
+        let mut stack: Vec<&T> = Vec::new();
+        bits.push(true);
+        for x in values {
+            while stack.last().is_some_and(|&top| top > x) {
+                stack.pop();
+                bits.push(false);
+            }
+            stack.push(x);
+            bits.push(true);
+        }
+        bits.extend(stack.iter().map(|_| false));
+        bits.push(false);
+        let bp = bits.iter().cloned().collect::<BitVec>();
+
+        let mut blocks = Vec::with_capacity(bits.len().div_ceil(BLOCK_BITS));
+        let mut supers = Vec::with_capacity(bits.len().div_ceil(SUPER_BITS));
+        let mut excess = 0;
+        for chunk in bits.chunks(SUPER_BITS) {
+            let base = excess;
+            let mut least = isize::MAX;
+            for block in chunk.chunks(BLOCK_BITS) {
+                let mut m = isize::MAX;
+                for &bit in block {
+                    excess += if bit { 1 } else { -1 };
+                    m = m.min(excess);
+                }
+                blocks.push((m - base) as i16);
+                least = least.min(m);
+            }
+            supers.push(least);
+        }
+
+        let mut table: Vec<Vec<u32>> = Vec::new();
+        let mut width = 1;
+        while 2 * width <= supers.len() {
+            let level = (0..supers.len() + 1 - 2 * width)
+                .map(|s| {
+                    let (a, b) = match table.last() {
+                        Some(prev) => (prev[s] as usize, prev[s + width] as usize),
+                        None => (s, s + 1),
+                    };
+                    if supers[b] <= supers[a] { b as u32 } else { a as u32 }
+                })
+                .collect();
+            table.push(level);
+            width *= 2;
+        }
+        Rmq { len: values.len(), bp, blocks, supers, table }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        let table = self.table.iter().fold(0, |acc, level| acc + level.len());
+        self.bp.size_in_bytes() + self.blocks.len() * 2 + self.supers.len() * 8 + table * 4
+    }
+
+    // Excess after the parenthesis at `p`.
+    fn excess(&self, p: usize) -> isize {
+        2 * self.bp.rank1(p + 1) as isize - (p + 1) as isize
+    }
+
+    // Excess before `p`.
+    fn start(&self, p: usize) -> isize {
+        if p == 0 { 0 } else { self.excess(p - 1) }
+    }
+
+    // Least excess in block `b`.
+    fn block_min(&self, b: usize) -> isize {
+        self.start(b / SUPER_BLOCKS * SUPER_BITS) + self.blocks[b] as isize
+    }
+
+    fn step(&self, p: usize) -> isize {
+        if self.bp.get(p) { 1 } else { -1 }
+    }
+
+    // Least excess after each position in `l..r`, a byte at a time where aligned.
+    fn scan_min(&self, l: usize, r: usize) -> isize {
+        let (mut e, mut m) = (self.start(l), isize::MAX);
+        let mut p = l;
+        while p < r {
+            if p.is_multiple_of(8) && p + 8 <= r {
+                let byte = self.bp.get_bits(p, 8) as usize;
+                m = m.min(e + MIN[byte] as isize);
+                e += EXCESS[byte] as isize;
+                p += 8;
+            } else {
+                e += self.step(p);
+                m = m.min(e);
+                p += 1;
+            }
+        }
+        m
+    }
+
+    // Rightmost position in `l..r` whose excess is `m`, where none is less,
+    // skipping bytes whose excess stays above it.
+    fn scan_rightmost(&self, l: usize, r: usize, m: isize) -> Option<usize> {
+        let mut e = self.start(l);
+        let mut found = None;
+        let mut p = l;
+        while p < r {
+            if p.is_multiple_of(8) && p + 8 <= r {
+                let byte = self.bp.get_bits(p, 8) as usize;
+                if e + (MIN[byte] as isize) > m {
+                    e += EXCESS[byte] as isize;
+                    p += 8;
+                    continue;
+                }
+            }
+            e += self.step(p);
+            if e == m {
+                found = Some(p);
+            }
+            p += 1;
+        }
+        found
+    }
+
+    // Rightmost superblock of the least excess in `sl..sr`, which must be nonempty.
+    fn super_min(&self, sl: usize, sr: usize) -> usize {
+        let k = (usize::BITS - 1 - (sr - sl).leading_zeros()) as usize;
+        if k == 0 {
+            return sl;
+        }
+        let level = &self.table[k - 1];
+        let (a, b) = (level[sl] as usize, level[sr - (1 << k)] as usize);
+        if self.supers[b] <= self.supers[a] { b } else { a }
+    }
+
+    // Least excess in blocks `bl..br`, scanning blocks of partial superblocks.
+    fn blocks_min(&self, bl: usize, br: usize) -> isize {
+        let (sl, sr) = (bl.div_ceil(SUPER_BLOCKS), br / SUPER_BLOCKS);
+        if sl >= sr {
+            return (bl..br).map(|b| self.block_min(b)).min().unwrap_or(isize::MAX);
+        }
+        let left = (bl..sl * SUPER_BLOCKS).map(|b| self.block_min(b));
+        let right = (sr * SUPER_BLOCKS..br).map(|b| self.block_min(b));
+        let m = left.chain(right).min().unwrap_or(isize::MAX);
+        m.min(self.supers[self.super_min(sl, sr)])
+    }
+
+    // Rightmost block in `bl..br` whose least excess is `m`, where none is less.
+    fn blocks_rightmost(&self, bl: usize, br: usize, m: isize) -> Option<usize> {
+        let (sl, sr) = (bl.div_ceil(SUPER_BLOCKS), br / SUPER_BLOCKS);
+        if sl >= sr {
+            return (bl..br).rev().find(|&b| self.block_min(b) == m);
+        }
+        (sr * SUPER_BLOCKS..br)
+            .rev()
+            .find(|&b| self.block_min(b) == m)
+            .or_else(|| {
+                let s = self.super_min(sl, sr);
+                if self.supers[s] != m {
+                    return None;
+                }
+                let blocks = s * SUPER_BLOCKS..(s + 1) * SUPER_BLOCKS;
+                blocks.rev().find(|&b| self.block_min(b) == m)
+            })
+            .or_else(|| (bl..sl * SUPER_BLOCKS).rev().find(|&b| self.block_min(b) == m))
+    }
+
+    // Least excess in `l..r`, with whole blocks from their summaries.
+    fn min_excess(&self, l: usize, r: usize) -> isize {
+        let (bl, br) = (l.div_ceil(BLOCK_BITS), r / BLOCK_BITS);
+        if bl >= br {
+            return self.scan_min(l, r);
+        }
+        let m = self.scan_min(l, bl * BLOCK_BITS).min(self.scan_min(br * BLOCK_BITS, r));
+        m.min(self.blocks_min(bl, br))
+    }
+
+    // Rightmost position in `l..r` whose excess is `m`, the least one in there.
+    fn rightmost(&self, l: usize, r: usize, m: isize) -> usize {
+        let (bl, br) = (l.div_ceil(BLOCK_BITS), r / BLOCK_BITS);
+        if bl >= br {
+            return self.scan_rightmost(l, r, m).unwrap();
+        }
+        self.scan_rightmost(br * BLOCK_BITS, r, m)
+            .or_else(|| {
+                let block = self.blocks_rightmost(bl, br, m)?;
+                self.scan_rightmost(block * BLOCK_BITS, (block + 1) * BLOCK_BITS, m)
+            })
+            .or_else(|| self.scan_rightmost(l, bl * BLOCK_BITS, m))
+            .unwrap()
+    }
+
+    // Position of the open parenthesis of the 'i+1'th value.
+    fn open(&self, i: usize) -> usize {
+        self.bp.select1(i + 1).unwrap()
+    }
+
+    /// Index of the leftmost minimum in `range`.
+    pub fn query(&self, range: Range<usize>) -> Option<usize> {
+        assert!(range.end <= self.len, "range out of bounds");
+        if range.start >= range.end {
+            return None;
+        }
+        let (i, j) = (range.start, range.end - 1);
+        if i == j {
+            return Some(i);
+        }
+        let (l, r) = (self.open(i), self.open(j));
+        let m = self.min_excess(l, r + 1);
+        if m >= self.excess(l) {
+            // No node in the range is shallower than the first one.
+            return Some(i);
+        }
+        // The closing parenthesis of the sibling before the answer.
+        let p = self.rightmost(l, r + 1, m);
+        Some(self.bp.rank1(p + 1) - 1)
+    }
+}
@@ -0,0 +1,67 @@
+extern crate rand;
+use self::rand::Rng;
+
+use super::*;
+
+fn leftmost_min(values: &[u32], range: Range<usize>) -> Option<usize> {
+    let start = range.start;
+    values[range].iter().enumerate().min_by_key(|&(_, v)| v).map(|(k, _)| start + k)
+}
+
+fn check(values: &[u32], queries: usize) {
+    let mut rng = rand::thread_rng();
+    let rmq = Rmq::new(values);
+    assert_eq!(rmq.len(), values.len());
+    let n = values.len();
+    for i in 0..n.min(20) {
+        for j in i..n.min(i + 20) + 1 {
+            assert_eq!(rmq.query(i..j), leftmost_min(values, i..j), "{}..{}", i, j);
+        }
+    }
+    for _ in 0..queries {
+        let i = rng.gen_range(0, n + 1);
+        let j = rng.gen_range(i, n + 1);
+        assert_eq!(rmq.query(i..j), leftmost_min(values, i..j), "{}..{}", i, j);
+    }
+    assert_eq!(rmq.query(0..n), leftmost_min(values, 0..n));
+}
+
+#[test]
+fn rmq_random() {
+    let mut rng = rand::thread_rng();
+    for &len in &[0, 1, 2, 300, 5000] {
+        // Few distinct values, so that minima tie.
+        for &max in &[2, 10, 1 << 20] {
+            let values = (0..len).map(|_| rng.gen_range(0, max)).collect::<Vec<u32>>();
+            check(&values, 2000);
+        }
+    }
+}
+
+#[test]
+fn rmq_monotone() {
+    let len = 3000;
+    let up = (0..len).collect::<Vec<u32>>();
+    let down = (0..len).rev().collect::<Vec<u32>>();
+    let same = vec![7; len as usize];
+    // Deep then shallow, so that minima are found across blocks.
+    let zigzag = (0..len)
+        .map(|i| if i % 1000 == 999 { 0 } else { i % 1000 + 1 })
+        .collect::<Vec<u32>>();
+    for values in &[up, down, same, zigzag] {
+        check(values, 2000);
+    }
+}
+
+#[test]
+fn rmq_superblocks() {
+    let mut rng = rand::thread_rng();
+    let len = 200_000;
+    // Parentheses span several superblocks, so ranges are answered from the sparse table.
+    for &max in &[3, 1 << 20] {
+        let values = (0..len).map(|_| rng.gen_range(0, max)).collect::<Vec<u32>>();
+        check(&values, 300);
+    }
+    let zigzag = (0..len).map(|i| if i % 50_000 == 49_999 { 0 } else { i % 50_000 + 1 });
+    check(&zigzag.collect::<Vec<u32>>(), 300);
+}
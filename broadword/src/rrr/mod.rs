@@ -0,0 +1,275 @@
+//! Entropy compressed static bit vector.
+//!
+//! Raman, Raman and Rao, "Succinct indexable dictionaries with applications
+//! to encoding k-ary trees and multisets", SODA 2002;
+//! Navarro and Providel, "Fast, small, simple rank/select on bitmaps", SEA 2012.
+
+use alloc::vec::Vec;
+
+use super::bitbuf::BitBuf;
+use super::{BitLen, Bits, Rank, Select0, Select1};
+
+#[cfg(test)]
+mod rrr_test;
+
+// Count of blocks per superblock.
+const SUPER: usize = 32;
+
+// `BINOM[n][k]` is n choose k, for `n < 64`.
+static BINOM: [[u64; 64]; 64] = binom();
+
+const fn binom() -> [[u64; 64]; 64] {
+    let mut table = [[0; 64]; 64];
+    let mut n = 0;
+    while n < 64 {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+}
+
+/// Static bit vector split into blocks of `B` bits, where `B < 64`, usually 15 or 63.
+///
+/// Each block is stored as its class, the count of its ones, and its offset,
+/// the rank of the block among the ones of its class.
+/// Blocks of all zeros or all ones take only a class,
+/// so sparse or clustered bits take less than one bit each.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rrr<const B: usize = 63> {
+    len: usize,
+    ones: usize,
+    classes: BitBuf,
+    offsets: BitBuf,
+    // Ones before each superblock, and its position in `offsets`.
+    ranks: Vec<usize>,
+    positions: Vec<usize>,
+}
+
+pub type Rrr15 = Rrr<15>;
+pub type Rrr63 = Rrr<63>;
+
+// A block being visited, `rank` ones and `pos` offset bits after the start.
+#[derive(Clone, Copy)]
+struct Cursor {
+    block: usize,
+    rank: usize,
+    pos: usize,
+}
+
+impl<const B: usize> Rrr<B> {
+    const CHECK: () = assert!(0 < B && B < 64, "block size must be in 1..64");
+
+    const CLASS_WIDTH: usize = 64 - B.leading_zeros() as usize;
+
+    // Bits of the offset of a block of class `k`.
+    fn width(k: usize) -> usize {
+        64 - (BINOM[B][k] - 1).leading_zeros() as usize
+    }
+
+    fn encode(block: u64) -> u64 {
+        let mut offset = 0;
+        let mut bits = block;
+        let mut j = 1;
+        while bits != 0 {
+            let p = bits.trailing_zeros() as usize;
+            offset += BINOM[p][j];
+            bits &= bits - 1;
+            j += 1;
+        }
+        offset
+    }
+
+    fn decode(k: usize, offset: u64) -> u64 {
+        let mut block = 0;
+        let mut offset = offset;
+        let mut p = B;
+        for j in (1..=k).rev() {
+            p -= 1;
+            while BINOM[p][j] > offset {
+                p -= 1;
+            }
+            block |= 1 << p;
+            offset -= BINOM[p][j];
+        }
+        block
+    }
+
+    /// Build from the first `len` bits of `words`.
+    pub fn from_words(words: &[u64], len: usize) -> Rrr<B> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CHECK;
+        let bits = BitBuf::from_words(words, len);
+        let mut rrr = Rrr { len, ..Rrr::default() };
+        let mut i = 0;
+        while i < len {
+            if (i / B).is_multiple_of(SUPER) {
+                rrr.ranks.push(rrr.ones);
+                rrr.positions.push(rrr.offsets.len());
+            }
+            let block = bits.get_bits(i, B.min(len - i));
+            let k = block.ones();
+            rrr.classes.push(k as u64, Self::CLASS_WIDTH);
+            rrr.offsets.push(Self::encode(block), Self::width(k));
+            rrr.ones += k;
+            i += B;
+        }
+        rrr
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Count non-zero bits.
+    pub fn ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Count zero bits.
+    pub fn zeros(&self) -> usize {
+        self.len - self.ones()
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        let index = (self.ranks.len() + self.positions.len()) * core::mem::size_of::<usize>();
+        self.classes.size_in_bytes() + self.offsets.size_in_bytes() + index
+    }
+
+    fn class(&self, block: usize) -> usize {
+        self.classes.get_bits(block * Self::CLASS_WIDTH, Self::CLASS_WIDTH) as usize
+    }
+
+    fn start(&self, superblock: usize) -> Cursor {
+        let block = superblock * SUPER;
+        Cursor { block, rank: self.ranks[superblock], pos: self.positions[superblock] }
+    }
+
+    // Move `cur` to the next block.
+    fn step(&self, cur: &mut Cursor) {
+        let k = self.class(cur.block);
+        cur.block += 1;
+        cur.rank += k;
+        cur.pos += Self::width(k);
+    }
+
+    fn block(&self, cur: &Cursor) -> u64 {
+        let k = self.class(cur.block);
+        match k {
+            0 => 0,
+            k if k == B => (1 << B) - 1,
+            k => Self::decode(k, self.offsets.get_bits(cur.pos, Self::width(k))),
+        }
+    }
+
+    // Cursor at the block of the 'i+1'th bit.
+    fn seek(&self, i: usize) -> Cursor {
+        let block = i / B;
+        let mut cur = self.start(block / SUPER);
+        while cur.block < block {
+            self.step(&mut cur);
+        }
+        cur
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "index out of bounds");
+        self.block(&self.seek(i)) & 1 << (i % B) != 0
+    }
+
+    // Last superblock for which `before(superblock) <= c`.
+    fn search<F: Fn(usize) -> usize>(&self, c: usize, before: F) -> usize {
+        let (mut lo, mut hi) = (0, self.ranks.len());
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if before(mid) <= c {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+}
+
+impl<const B: usize> Bits for Rrr<B> {
+    const SIZE: usize = usize::MAX;
+
+    fn none() -> Self {
+        Rrr::default()
+    }
+
+    fn ones(&self) -> usize {
+        Rrr::<B>::ones(self)
+    }
+
+    fn zeros(&self) -> usize {
+        Rrr::<B>::zeros(self)
+    }
+}
+
+impl<const B: usize> BitLen for Rrr<B> {
+    fn len(&self) -> usize {
+        Rrr::<B>::len(self)
+    }
+
+    fn ones(&self) -> usize {
+        Rrr::<B>::ones(self)
+    }
+
+    fn zeros(&self) -> usize {
+        Rrr::<B>::zeros(self)
+    }
+}
+
+impl<const B: usize> Rank for Rrr<B> {
+    fn rank1(&self, i: usize) -> usize {
+        if i >= self.len {
+            return self.ones;
+        }
+        let cur = self.seek(i);
+        let rank: usize = self.block(&cur).rank1(i % B);
+        cur.rank + rank
+    }
+
+    fn rank0(&self, i: usize) -> usize {
+        i.min(self.len) - self.rank1(i)
+    }
+}
+
+impl<const B: usize> Select1 for Rrr<B> {
+    fn select1(&self, c: usize) -> Option<usize> {
+        if c >= self.ones {
+            return None;
+        }
+        let mut cur = self.start(self.search(c, |s| self.ranks[s]));
+        while cur.rank + self.class(cur.block) <= c {
+            self.step(&mut cur);
+        }
+        let p: usize = self.block(&cur).select1(c - cur.rank)?;
+        Some(cur.block * B + p)
+    }
+}
+
+impl<const B: usize> Select0 for Rrr<B> {
+    fn select0(&self, c: usize) -> Option<usize> {
+        if c >= self.len - self.ones {
+            return None;
+        }
+        let zeros = |cur: &Cursor| cur.block * B - cur.rank;
+        let mut cur = self.start(self.search(c, |s| s * SUPER * B - self.ranks[s]));
+        while zeros(&cur) + B - self.class(cur.block) <= c {
+            self.step(&mut cur);
+        }
+        let p: usize = self.block(&cur).select0(c - zeros(&cur))?;
+        Some(cur.block * B + p)
+    }
+}
@@ -0,0 +1,85 @@
+extern crate rand;
+use self::rand::Rng;
+
+use super::*;
+
+// Clustered bits: runs of ones and zeros, with some noise.
+fn gen<R: Rng>(len: usize, rng: &mut R) -> Vec<u64> {
+    let mut words = vec![0u64; len.div_ceil(64) + 1];
+    let mut i = 0;
+    while i < len {
+        let run = rng.gen_range(1, 300);
+        if rng.gen_weighted_bool(4) {
+            for j in i..(i + run).min(len) {
+                words[j / 64] |= 1 << (j % 64);
+            }
+        } else if rng.gen_weighted_bool(2) {
+            let j = rng.gen_range(i, (i + run).min(len));
+            words[j / 64] |= 1 << (j % 64);
+        }
+        i += run;
+    }
+    words
+}
+
+fn check<const B: usize>(words: &[u64], len: usize) {
+    let rrr = Rrr::<B>::from_words(words, len);
+    let bits = (0..len).map(|i| words[i / 64] & 1 << (i % 64) != 0).collect::<Vec<bool>>();
+    let ones = bits.iter().filter(|&&b| b).count();
+    assert_eq!(rrr.len(), len);
+    assert_eq!(rrr.ones(), ones);
+    assert_eq!(rrr.zeros(), len - ones);
+    assert_eq!(BitLen::len(&rrr), len);
+    assert_eq!(BitLen::zeros(&rrr), len - ones);
+    assert_eq!(Bits::ones(&rrr), ones);
+    assert_eq!(Bits::zeros(&rrr), len - ones);
+
+    let (mut rank1, mut rank0) = (0, 0);
+    for (i, &b) in bits.iter().enumerate() {
+        assert_eq!(rrr.get(i), b);
+        assert_eq!(rrr.rank1(i), rank1);
+        assert_eq!(rrr.rank0(i), rank0);
+        if b {
+            assert_eq!(rrr.select1(rank1), Some(i));
+            rank1 += 1;
+        } else {
+            assert_eq!(rrr.select0(rank0), Some(i));
+            rank0 += 1;
+        }
+    }
+    assert_eq!(rrr.rank1(len), ones);
+    assert_eq!(rrr.rank0(len + 10), len - ones);
+    assert_eq!(rrr.select1(ones), None);
+    assert_eq!(rrr.select0(len - ones), None);
+}
+
+#[test]
+fn rrr_rank_select() {
+    let mut rng = rand::thread_rng();
+    for &len in &[0, 1, 15, 63, 64, 100, 1000, 100_000] {
+        let words = gen(len, &mut rng);
+        check::<15>(&words, len);
+        check::<63>(&words, len);
+        check::<31>(&words, len);
+
+        let ones = vec![!0u64; len.div_ceil(64)];
+        check::<15>(&ones, len);
+        check::<63>(&ones, len);
+    }
+}
+
+#[test]
+fn rrr_compression() {
+    let mut rng = rand::thread_rng();
+    let len = 1 << 20;
+    let mut words = vec![0u64; len / 64];
+    for _ in 0..1000 {
+        let i = rng.gen_range(0, len - 100);
+        for j in i..i + 100 {
+            words[j / 64] |= 1 << (j % 64);
+        }
+    }
+    let rrr = Rrr63::from_words(&words, len);
+    assert!(rrr.size_in_bytes() < len / 8 / 4);
+    assert_eq!(rrr.ones(), words.iter().map(|w| w.ones()).sum::<usize>());
+}
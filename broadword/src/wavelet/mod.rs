@@ -0,0 +1,110 @@
+//! Sequence of bytes with rank and select of any byte.
+//!
+//! Claude, Navarro and Ordóñez, "The wavelet matrix", SPIRE 2012.
+
+use alloc::vec::Vec;
+
+use super::bitvec::BitVec;
+use super::{Rank, Select0, Select1};
+
+#[cfg(test)]
+mod wavelet_test;
+
+const LEVELS: usize = 8;
+
+/// Wavelet tree of bytes in its matrix layout.
+///
+/// Level `l` holds bit `7 - l` of each byte, in the order left by stably sorting
+/// on the bits above it, so that every level is a single bit vector.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WaveletMatrix {
+    len: usize,
+    levels: Vec<BitVec>,
+    // Count of zeros of each level.
+    zeros: Vec<usize>,
+}
+
+impl WaveletMatrix {
+    pub fn new(bytes: &[u8]) -> WaveletMatrix {
+        let mut levels = Vec::with_capacity(LEVELS);
+        let mut zeros = Vec::with_capacity(LEVELS);
+        let mut cur = bytes.to_vec();
+        let mut next = Vec::with_capacity(bytes.len());
+        for l in 0..LEVELS {
+            let bit = LEVELS - 1 - l;
+            let level = cur.iter().map(|&c| c >> bit & 1 != 0).collect::<BitVec>();
+            next.clear();
+            next.extend(cur.iter().filter(|&&c| c >> bit & 1 == 0));
+            zeros.push(next.len());
+            next.extend(cur.iter().filter(|&&c| c >> bit & 1 != 0));
+            levels.push(level);
+            core::mem::swap(&mut cur, &mut next);
+        }
+        WaveletMatrix { len: bytes.len(), levels, zeros }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the 'i+1'th byte.
+    pub fn get(&self, i: usize) -> u8 {
+        assert!(i < self.len, "index out of bounds");
+        let mut i = i;
+        let mut c = 0;
+        for (level, &zeros) in self.levels.iter().zip(&self.zeros) {
+            if level.get(i) {
+                c = c << 1 | 1;
+                i = zeros + level.rank1(i);
+            } else {
+                c <<= 1;
+                i = level.rank0(i);
+            }
+        }
+        c
+    }
+
+    /// Count occurrences of `c` before `i`.
+    pub fn rank(&self, c: u8, i: usize) -> usize {
+        let (mut start, mut end) = (0, i.min(self.len));
+        for (l, (level, &zeros)) in self.levels.iter().zip(&self.zeros).enumerate() {
+            if c >> (LEVELS - 1 - l) & 1 != 0 {
+                start = zeros + level.rank1(start);
+                end = zeros + level.rank1(end);
+            } else {
+                start = level.rank0(start);
+                end = level.rank0(end);
+            }
+        }
+        end - start
+    }
+
+    /// Return the index of the 'k+1'th occurrence of `c`.
+    pub fn select(&self, c: u8, k: usize) -> Option<usize> {
+        if k >= self.rank(c, self.len) {
+            return None;
+        }
+        // Find where the occurrences of `c` start on the last level, then go up.
+        let mut start = 0;
+        for (l, (level, &zeros)) in self.levels.iter().zip(&self.zeros).enumerate() {
+            if c >> (LEVELS - 1 - l) & 1 != 0 {
+                start = zeros + level.rank1(start);
+            } else {
+                start = level.rank0(start);
+            }
+        }
+        let mut i = start + k;
+        for (l, (level, &zeros)) in self.levels.iter().zip(&self.zeros).enumerate().rev() {
+            i = if c >> (LEVELS - 1 - l) & 1 != 0 {
+                level.select1(i - zeros).unwrap()
+            } else {
+                level.select0(i).unwrap()
+            };
+        }
+        Some(i)
+    }
+}
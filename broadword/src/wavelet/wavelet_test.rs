@@ -0,0 +1,29 @@
+extern crate rand;
+use self::rand::Rng;
+
+use super::*;
+
+#[test]
+fn wavelet_rank_select() {
+    let mut rng = rand::thread_rng();
+    for &len in &[0, 1, 100, 5000] {
+        // Small alphabets, and all bytes.
+        for &sigma in &[2, 5, 256] {
+            let bytes = (0..len).map(|_| rng.gen_range(0, sigma) as u8).collect::<Vec<u8>>();
+            let wm = WaveletMatrix::new(&bytes);
+            assert_eq!(wm.len(), len);
+
+            let mut counts = [0; 256];
+            for (i, &c) in bytes.iter().enumerate() {
+                assert_eq!(wm.get(i), c);
+                assert_eq!(wm.rank(c, i), counts[c as usize]);
+                assert_eq!(wm.select(c, counts[c as usize]), Some(i));
+                counts[c as usize] += 1;
+            }
+            for c in 0..=255u8 {
+                assert_eq!(wm.rank(c, len), counts[c as usize]);
+                assert_eq!(wm.select(c, counts[c as usize]), None);
+            }
+        }
+    }
+}